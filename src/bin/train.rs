@@ -0,0 +1,234 @@
+use snake_neat::config::Config;
+use snake_neat::neat::checkpoint::Checkpoint;
+use snake_neat::neat::genome::Genome;
+use snake_neat::neat::genome_file::GenomeFile;
+use snake_neat::neat::population::Population;
+use snake_neat::neat::stats::{GenerationStats, StatsLog};
+use snake_neat::neat::task;
+use snake_neat::neat::task::{Outcome, Task};
+use snake_neat::neat::Fitness;
+use snake_neat::tasks;
+use std::io::ErrorKind;
+use std::time::Instant;
+
+struct Args {
+    generations: i32,
+    task: String,
+    checkpoint: Option<String>,
+    every: i32,
+    resume: Option<String>,
+    from_genome: Option<String>,
+    seed: Option<u64>,
+    config: Option<String>,
+    stats: String,
+}
+impl Args {
+    fn parse() -> Self {
+        let mut args = Self {
+            generations: 100,
+            task: "snake".to_string(),
+            checkpoint: None,
+            every: 10,
+            resume: None,
+            from_genome: None,
+            seed: None,
+            config: None,
+            stats: "stats.csv".to_string(),
+        };
+        let mut positional = 0;
+        let mut iter = std::env::args().skip(1);
+        while let Some(arg) = iter.next() {
+            let mut value = || {
+                iter.next()
+                    .unwrap_or_else(|| panic!("{} expects a value", arg))
+            };
+            match arg.as_str() {
+                "--checkpoint" => args.checkpoint = Some(value()),
+                "--every" => args.every = value().parse().expect("--every must be a number"),
+                "--resume" => args.resume = Some(value()),
+                "--from-genome" => args.from_genome = Some(value()),
+                "--config" => args.config = Some(value()),
+                "--stats" => args.stats = value(),
+                "--seed" => args.seed = Some(value().parse().expect("--seed must be a number")),
+                _ if positional == 0 => {
+                    args.generations = arg.parse().expect("generations must be a number");
+                    positional += 1;
+                }
+                _ if positional == 1 => {
+                    args.task = arg;
+                    positional += 1;
+                }
+                _ => panic!("unexpected argument {}", arg),
+            }
+        }
+        args
+    }
+}
+fn main() {
+    let args = Args::parse();
+    let config = match args.config.as_ref() {
+        Some(path) => Config::load(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1)
+        }),
+        None => Config::default(),
+    };
+    let task = tasks::by_name(&args.task, &config).unwrap_or_else(|| {
+        panic!(
+            "unknown task {}, expected one of {:?}",
+            args.task,
+            tasks::NAMES
+        )
+    });
+    let (mut population, mut best, mut bests, mut averages) = match args.resume.as_ref() {
+        Some(path) => {
+            let checkpoint = Checkpoint::load(path).unwrap_or_else(|e| panic!("{}", e));
+            let environment = &checkpoint.population.environment;
+            if environment.input_size != task.input_size()
+                || environment.output_size != task.output_size()
+            {
+                panic!(
+                    "checkpoint {} was not written by the {} task",
+                    path,
+                    task.name()
+                );
+            }
+            println!(
+                "resuming {} from generation {}",
+                path, checkpoint.population.generation
+            );
+            (
+                checkpoint.population,
+                checkpoint.best,
+                checkpoint.bests,
+                checkpoint.averages,
+            )
+        }
+        None => {
+            let mut environment = task.environment();
+            config.apply(&mut environment);
+            let seed = args.seed.or(config.seed).unwrap_or_else(rand::random);
+            let population = match args.from_genome.as_ref() {
+                Some(path) => {
+                    let genome = GenomeFile::load(path, &environment)
+                        .unwrap_or_else(|e| panic!("{}: {}", path, e));
+                    Population::from_genome(environment, &genome, seed)
+                }
+                None => Population::seeded(environment, seed),
+            };
+            println!("seed: {}", seed);
+            (population, None, vec![], vec![])
+        }
+    };
+    let mut stats = StatsLog::open(&args.stats, &task.metrics()).unwrap_or_else(|e| {
+        // a log of another run stays untouched
+        if e.kind() == ErrorKind::InvalidData {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        panic!("failed to open stats log {}: {}", args.stats, e)
+    });
+    let start = Instant::now();
+    for _ in 0..args.generations {
+        let generation_start = Instant::now();
+        let outcomes = evaluate_population(&population, task.as_ref());
+        let fitnesses = outcomes.iter().map(|o| o.fitness).collect::<Vec<_>>();
+        let (current_idx, current_best) = fitnesses
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .unwrap();
+        bests.push(current_best);
+        if best
+            .as_ref()
+            .is_none_or(|b: &(Genome, Fitness)| current_best > b.1)
+        {
+            best.replace((population.genomes[current_idx].clone(), current_best));
+        }
+        let generation_stats = GenerationStats::collect(
+            &population,
+            &outcomes,
+            generation_start.elapsed(),
+            start.elapsed(),
+        );
+        if let Err(e) = stats.write(&generation_stats) {
+            println!("failed to write stats log {}: {}", args.stats, e);
+        }
+        let average = population.evolve(&fitnesses);
+        averages.push(average);
+        println!(
+            "Gen: {} | Best: {:.02} | Average: {:.02} | Species: {} | {:.02?}",
+            population.generation,
+            current_best,
+            average,
+            population.species.len(),
+            start.elapsed()
+        );
+        if let Some(path) = args.checkpoint.as_ref() {
+            if args.every > 0 && population.generation % args.every == 0 {
+                save(path, &population, &best, &bests, &averages);
+            }
+        }
+        if task.solved(current_best) {
+            println!(
+                "{} solved in {} generations",
+                task.name(),
+                population.generation
+            );
+            break;
+        }
+    }
+    if let Some(path) = args.checkpoint.as_ref() {
+        save(path, &population, &best, &bests, &averages);
+    }
+    if let Some((genome, _)) = best {
+        let file = GenomeFile::new(genome, &population.environment);
+        if let Err(e) = file.save("best-genome.json") {
+            println!("failed to write best-genome.json: {}", e);
+        }
+    }
+    std::fs::write("bests.txt", format!("{:?}", bests)).unwrap();
+    std::fs::write("averages.txt", format!("{:?}", averages)).unwrap();
+}
+fn save(
+    path: &str,
+    population: &Population,
+    best: &Option<(Genome, Fitness)>,
+    bests: &[Fitness],
+    averages: &[Fitness],
+) {
+    let checkpoint = Checkpoint::new(
+        population.clone(),
+        best.clone(),
+        bests.to_vec(),
+        averages.to_vec(),
+    );
+    if let Err(e) = checkpoint.save(path) {
+        println!("failed to write checkpoint {}: {}", path, e);
+    }
+}
+fn evaluate_population(population: &Population, task: &dyn Task) -> Vec<Outcome> {
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let chunk_size = population.genomes.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let handles = population
+            .genomes
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(c, chunk)| {
+                scope.spawn(move || {
+                    (0..chunk.len())
+                        .map(|i| task::evaluate(task, population, c * chunk_size + i))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    })
+}
@@ -0,0 +1,477 @@
+use crate::neat::environment::Environment;
+use crate::neat::node::ActivationFunction;
+use crate::neat::task::Aggregation;
+use crate::neat::Generation;
+use crate::snake::fitness::FitnessKind;
+use crate::snake::level::Level;
+use crate::snake::sim::{Direction, GameGrid, Limits, Location, Start};
+use crate::snake::{ActionEncoding, Observation, Reward, SnakeTask};
+use serde::Deserialize;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+// every field is optional, anything left out keeps the task's own default
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub neat: NeatConfig,
+    #[serde(default)]
+    pub snake: SnakeConfig,
+    // read from snake.level by load
+    #[serde(skip)]
+    pub level: Option<Level>,
+}
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct NeatConfig {
+    pub population_count: Option<i32>,
+    pub c1: Option<f32>,
+    pub c2: Option<f32>,
+    pub c3: Option<f32>,
+    pub compatibility_threshold: Option<f32>,
+    pub stagnation_threshold: Option<Generation>,
+    pub only_mutate: Option<f32>,
+    pub elitism: Option<f32>,
+    pub crossover_only: Option<f32>,
+    pub inherit_disable: Option<f32>,
+    pub add_connection: Option<f32>,
+    pub connection_weight: Option<f32>,
+    pub perturb: Option<f32>,
+    pub add_node: Option<f32>,
+    pub recurrent: Option<bool>,
+    pub feed_forward: Option<bool>,
+    pub activation_functions: Option<Vec<ActivationFunction>>,
+    pub mutate_activation: Option<f32>,
+    pub trials: Option<i32>,
+    pub aggregation: Option<Aggregation>,
+    pub stddev_penalty: Option<f32>,
+    pub shared_food: Option<bool>,
+}
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SnakeConfig {
+    pub max_turns: Option<i32>,
+    pub grid_width: Option<i32>,
+    pub grid_height: Option<i32>,
+    pub wrap: Option<bool>,
+    pub food_reward: Option<f32>,
+    pub towards_food_reward: Option<f32>,
+    pub can_move_towards_food_reward: Option<f32>,
+    pub starvation_turns: Option<i32>,
+    pub starvation_per_segment: Option<i32>,
+    pub detect_loops: Option<bool>,
+    pub observation: Option<Observation>,
+    pub actions: Option<ActionEncoding>,
+    pub fitness: Option<FitnessKind>,
+    pub start_length: Option<i32>,
+    pub start_x: Option<i32>,
+    pub start_y: Option<i32>,
+    pub start_heading: Option<Direction>,
+    pub random_start: Option<bool>,
+    pub food_x: Option<i32>,
+    pub food_y: Option<i32>,
+    pub random_food: Option<bool>,
+    pub level: Option<String>,
+}
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::Io(path.display().to_string(), e))?;
+        let mut config = toml::from_str::<Self>(&contents)
+            .map_err(|e| ConfigError::Parse(path.display().to_string(), e))?;
+        if let Some(level) = config.snake.level.as_ref() {
+            let loaded = Level::load(level)
+                .map_err(|e| ConfigError::Invalid(format!("snake.level {}: {}", level, e)))?;
+            config.level = Some(loaded);
+        }
+        config.validate()?;
+        Ok(config)
+    }
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let neat = &self.neat;
+        let probabilities = [
+            ("neat.only_mutate", neat.only_mutate),
+            ("neat.elitism", neat.elitism),
+            ("neat.crossover_only", neat.crossover_only),
+            ("neat.inherit_disable", neat.inherit_disable),
+            ("neat.add_connection", neat.add_connection),
+            ("neat.connection_weight", neat.connection_weight),
+            ("neat.perturb", neat.perturb),
+            ("neat.add_node", neat.add_node),
+            ("neat.mutate_activation", neat.mutate_activation),
+        ];
+        for (name, value) in probabilities {
+            if let Some(value) = value {
+                if !(0.0..=1.0).contains(&value) {
+                    return Err(ConfigError::Invalid(format!(
+                        "{} is {} but must be within [0, 1]",
+                        name, value
+                    )));
+                }
+            }
+        }
+        let positive = [
+            ("neat.population_count", neat.population_count),
+            ("neat.stagnation_threshold", neat.stagnation_threshold),
+            ("neat.trials", neat.trials),
+            ("snake.max_turns", self.snake.max_turns),
+            ("snake.grid_width", self.snake.grid_width),
+            ("snake.grid_height", self.snake.grid_height),
+            ("snake.start_length", self.snake.start_length),
+        ];
+        for (name, value) in positive {
+            if let Some(value) = value {
+                if value <= 0 {
+                    return Err(ConfigError::Invalid(format!(
+                        "{} is {} but must be greater than 0",
+                        name, value
+                    )));
+                }
+            }
+        }
+        for (name, value) in [
+            ("neat.c1", neat.c1),
+            ("neat.c2", neat.c2),
+            ("neat.c3", neat.c3),
+            ("neat.compatibility_threshold", neat.compatibility_threshold),
+            ("neat.stddev_penalty", neat.stddev_penalty),
+            (
+                "snake.starvation_turns",
+                self.snake.starvation_turns.map(|t| t as f32),
+            ),
+            (
+                "snake.starvation_per_segment",
+                self.snake.starvation_per_segment.map(|t| t as f32),
+            ),
+        ] {
+            if let Some(value) = value {
+                if value < 0.0 {
+                    return Err(ConfigError::Invalid(format!(
+                        "{} is {} but must not be negative",
+                        name, value
+                    )));
+                }
+            }
+        }
+        if neat
+            .activation_functions
+            .as_ref()
+            .is_some_and(|f| f.is_empty())
+        {
+            return Err(ConfigError::Invalid(
+                "neat.activation_functions must list at least one function".to_string(),
+            ));
+        }
+        if neat.recurrent == Some(true) && neat.feed_forward == Some(true) {
+            return Err(ConfigError::Invalid(
+                "neat.recurrent and neat.feed_forward cannot both be set".to_string(),
+            ));
+        }
+        if let Some(Observation::Window { size, .. }) = self.snake.observation {
+            if size < 1 || size % 2 == 0 {
+                return Err(ConfigError::Invalid(format!(
+                    "snake.observation window size is {} but must be odd and positive",
+                    size
+                )));
+            }
+        }
+        if self.snake.start_x.is_some() != self.snake.start_y.is_some() {
+            return Err(ConfigError::Invalid(
+                "snake.start_x and snake.start_y must be set together".to_string(),
+            ));
+        }
+        if self.snake.food_x.is_some() != self.snake.food_y.is_some() {
+            return Err(ConfigError::Invalid(
+                "snake.food_x and snake.food_y must be set together".to_string(),
+            ));
+        }
+        if self.snake.food_x.is_some()
+            && (self.snake.random_start == Some(true) || self.snake.random_food == Some(true))
+        {
+            return Err(ConfigError::Invalid(
+                "snake.food_x / snake.food_y cannot be set with snake.random_start or snake.random_food".to_string(),
+            ));
+        }
+        if self.snake.food_x.is_some() && self.neat.shared_food == Some(true) {
+            return Err(ConfigError::Invalid(
+                "snake.food_x / snake.food_y cannot be set with neat.shared_food, every food comes from the shared sequence".to_string(),
+            ));
+        }
+        if self.level.is_some()
+            && (self.snake.grid_width.is_some() || self.snake.grid_height.is_some())
+        {
+            return Err(ConfigError::Invalid(
+                "snake.grid_width / snake.grid_height cannot be set with snake.level, the level sets the grid".to_string(),
+            ));
+        }
+        let task = self.snake_task();
+        if task.start.random && !task.start.fits(task.grid, &task.walls) {
+            return Err(ConfigError::Invalid(format!(
+                "no random start fits {} segments on the {} x {} grid without touching a wall",
+                task.start.length, task.grid.grid.0, task.grid.grid.1
+            )));
+        }
+        if !task.start.fits(task.grid, &task.walls) {
+            let position = match task.start.position {
+                Some(l) => format!("({}, {})", l.x, l.y),
+                None => "the default position".to_string(),
+            };
+            return Err(ConfigError::Invalid(format!(
+                "the starting snake ({} segments at {} heading {:?}) does not fit on the {} x {} grid without touching a wall or itself",
+                task.start.length,
+                position,
+                task.start.heading,
+                task.grid.grid.0,
+                task.grid.grid.1
+            )));
+        }
+        if let Some(food) = task.start.food {
+            if !task.start.random
+                && !task.start.random_food
+                && !task.start.food_fits(task.grid, &task.walls)
+            {
+                return Err(ConfigError::Invalid(format!(
+                    "the first food at ({}, {}) is off the {} x {} grid, on a wall or under the starting snake",
+                    food.x, food.y, task.grid.grid.0, task.grid.grid.1
+                )));
+            }
+        }
+        Ok(())
+    }
+    pub fn apply(&self, environment: &mut Environment) {
+        let neat = &self.neat;
+        let set = |target: &mut f32, value: Option<f32>| {
+            if let Some(value) = value {
+                *target = value;
+            }
+        };
+        if let Some(count) = neat.population_count {
+            environment.population_count = count;
+        }
+        set(&mut environment.compatibility_factors.c1, neat.c1);
+        set(&mut environment.compatibility_factors.c2, neat.c2);
+        set(&mut environment.compatibility_factors.c3, neat.c3);
+        set(
+            &mut environment.compatibility_threshold,
+            neat.compatibility_threshold,
+        );
+        if let Some(threshold) = neat.stagnation_threshold {
+            environment.stagnation_threshold = threshold;
+        }
+        set(&mut environment.only_mutate, neat.only_mutate);
+        set(&mut environment.elitism, neat.elitism);
+        set(&mut environment.crossover_only, neat.crossover_only);
+        set(&mut environment.inherit_disable, neat.inherit_disable);
+        set(&mut environment.add_connection, neat.add_connection);
+        set(&mut environment.connection_weight, neat.connection_weight);
+        set(&mut environment.perturb, neat.perturb);
+        set(&mut environment.add_node, neat.add_node);
+        set(&mut environment.mutate_activation, neat.mutate_activation);
+        if let Some(recurrent) = neat.recurrent {
+            environment.recurrent = recurrent;
+        }
+        if let Some(feed_forward) = neat.feed_forward {
+            environment.feed_forward = feed_forward;
+        }
+        if let Some(functions) = neat.activation_functions.as_ref() {
+            environment.activation_functions = functions.clone();
+        }
+        if let Some(trials) = neat.trials {
+            environment.trials = trials;
+        }
+        if let Some(aggregation) = neat.aggregation {
+            environment.aggregation = aggregation;
+        }
+        set(&mut environment.stddev_penalty, neat.stddev_penalty);
+        if let Some(shared_food) = neat.shared_food {
+            environment.shared_food = shared_food;
+        }
+        if let Some(max_turns) = self.snake.max_turns {
+            environment.max_turns = max_turns;
+        }
+    }
+    pub fn game_grid(&self, default: GameGrid) -> GameGrid {
+        let mut grid = GameGrid::new(
+            self.snake.grid_width.unwrap_or(default.grid.0),
+            self.snake.grid_height.unwrap_or(default.grid.1),
+        );
+        grid.wrap = self.snake.wrap.unwrap_or(default.wrap);
+        grid
+    }
+    pub fn limits(&self, default: Limits) -> Limits {
+        Limits {
+            starvation: self.snake.starvation_turns.unwrap_or(default.starvation),
+            starvation_per_segment: self
+                .snake
+                .starvation_per_segment
+                .unwrap_or(default.starvation_per_segment),
+            detect_loops: self.snake.detect_loops.unwrap_or(default.detect_loops),
+        }
+    }
+    pub fn start(&self, default: Start) -> Start {
+        let snake = &self.snake;
+        let position = match (snake.start_x, snake.start_y) {
+            (Some(x), Some(y)) => Some(Location::new(x, y)),
+            _ => default.position,
+        };
+        let food = match (snake.food_x, snake.food_y) {
+            (Some(x), Some(y)) => Some(Location::new(x, y)),
+            _ => default.food,
+        };
+        Start {
+            length: snake.start_length.unwrap_or(default.length),
+            position,
+            heading: snake.start_heading.unwrap_or(default.heading),
+            random: snake.random_start.unwrap_or(default.random),
+            random_food: snake.random_food.unwrap_or(default.random_food),
+            food,
+            shared_food: default.shared_food,
+        }
+    }
+    pub fn reward(&self, default: Reward) -> Reward {
+        Reward::new(
+            self.snake
+                .food_reward
+                .unwrap_or(default.food_collection_reward),
+            self.snake
+                .towards_food_reward
+                .unwrap_or(default.towards_food_reward),
+            self.snake
+                .can_move_towards_food_reward
+                .unwrap_or(default.can_move_towards_food_reward),
+        )
+    }
+    // a level replaces the default board, start and first food, the other keys still apply on top
+    pub fn snake_task(&self) -> SnakeTask {
+        let mut default = SnakeTask::default();
+        if let Some(level) = self.level.as_ref() {
+            default.grid = level.grid;
+            default.walls = level.walls.clone();
+            if let Some((position, heading)) = level.start {
+                default.start.position = Some(position);
+                default.start.heading = heading;
+            }
+            default.start.food = level.food;
+        }
+        SnakeTask {
+            grid: self.game_grid(default.grid),
+            walls: default.walls,
+            limits: self.limits(default.limits),
+            observation: self.snake.observation.unwrap_or(default.observation),
+            actions: self.snake.actions.unwrap_or(default.actions),
+            fitness: self.snake.fitness.unwrap_or(default.fitness),
+            start: self.start(default.start),
+            reward: self.reward(default.reward),
+        }
+    }
+}
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(String, std::io::Error),
+    Parse(String, toml::de::Error),
+    Invalid(String),
+}
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(path, e) => write!(f, "cannot read config {}: {}", path, e),
+            ConfigError::Parse(path, e) => write!(f, "cannot parse config {}: {}", path, e),
+            ConfigError::Invalid(reason) => write!(f, "invalid config: {}", reason),
+        }
+    }
+}
+impl std::error::Error for ConfigError {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml: &str) -> Result<Config, ConfigError> {
+        let config = toml::from_str::<Config>(toml)
+            .map_err(|e| ConfigError::Parse("test".to_string(), e))?;
+        config.validate()?;
+        Ok(config)
+    }
+    #[test]
+    fn empty_config_is_valid() {
+        assert!(parse("").is_ok());
+    }
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(matches!(
+            parse("[neat]\npopulation = 10"),
+            Err(ConfigError::Parse(..))
+        ));
+    }
+    #[test]
+    fn probabilities_must_be_within_unit_range() {
+        assert!(parse("[neat]\nelitism = 0.5").is_ok());
+        assert!(matches!(
+            parse("[neat]\nelitism = 1.5"),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+    #[test]
+    fn counts_must_be_positive() {
+        assert!(matches!(
+            parse("[neat]\npopulation_count = 0"),
+            Err(ConfigError::Invalid(_))
+        ));
+        assert!(matches!(
+            parse("[snake]\nmax_turns = -1"),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+    #[test]
+    fn recurrent_and_feed_forward_exclude_each_other() {
+        assert!(matches!(
+            parse("[neat]\nrecurrent = true\nfeed_forward = true"),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+    #[test]
+    fn apply_overrides_only_the_given_keys() {
+        let config = parse("[neat]\npopulation_count = 20\n[snake]\nmax_turns = 100").unwrap();
+        let mut environment = Environment::new();
+        let c1 = environment.compatibility_factors.c1;
+        config.apply(&mut environment);
+        assert_eq!(environment.population_count, 20);
+        assert_eq!(environment.max_turns, 100);
+        assert_eq!(environment.compatibility_factors.c1, c1);
+    }
+    #[test]
+    fn start_must_fit_on_the_board() {
+        assert!(parse("[snake]\nstart_length = 1").is_ok());
+        assert!(matches!(
+            parse("[snake]\nstart_x = 10"),
+            Err(ConfigError::Invalid(_))
+        ));
+        // the default 6 segments trail off the left edge
+        assert!(matches!(
+            parse("[snake]\nstart_x = 2\nstart_y = 15"),
+            Err(ConfigError::Invalid(_))
+        ));
+    }
+    #[test]
+    fn first_food_must_be_free() {
+        let config = parse("[snake]\nfood_x = 3\nfood_y = 4").unwrap();
+        assert_eq!(config.snake_task().start.food, Some(Location::new(3, 4)));
+        for toml in [
+            "[snake]\nfood_x = 3",
+            "[snake]\nfood_x = 30\nfood_y = 0",
+            // the default body runs from (10, 15) back to (5, 15)
+            "[snake]\nfood_x = 7\nfood_y = 15",
+            "[snake]\nfood_x = 3\nfood_y = 4\nrandom_food = true",
+            "[snake]\nfood_x = 3\nfood_y = 4\nrandom_start = true",
+            "[neat]\nshared_food = true\n[snake]\nfood_x = 3\nfood_y = 4",
+        ] {
+            assert!(
+                matches!(parse(toml), Err(ConfigError::Invalid(_))),
+                "{}",
+                toml
+            );
+        }
+    }
+}
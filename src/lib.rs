@@ -0,0 +1,4 @@
+pub mod config;
+pub mod neat;
+pub mod snake;
+pub mod tasks;
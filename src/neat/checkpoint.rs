@@ -0,0 +1,140 @@
+use crate::neat::genome::Genome;
+use crate::neat::population::Population;
+use crate::neat::Fitness;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+// bump when the layout of anything inside Population changes
+pub const CHECKPOINT_VERSION: u32 = 3;
+
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub version: u32,
+    pub population: Population,
+    pub best: Option<(Genome, Fitness)>,
+    pub bests: Vec<Fitness>,
+    pub averages: Vec<Fitness>,
+}
+impl Checkpoint {
+    pub fn new(
+        population: Population,
+        best: Option<(Genome, Fitness)>,
+        bests: Vec<Fitness>,
+        averages: Vec<Fitness>,
+    ) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            population,
+            best,
+            bests,
+            averages,
+        }
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), CheckpointError> {
+        // write next to the target then rename so a crash mid-write keeps the last checkpoint
+        let path = path.as_ref();
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, CheckpointError> {
+        let contents = std::fs::read_to_string(path)?;
+        let version = serde_json::from_str::<Version>(&contents)?.version;
+        if version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::Version(version));
+        }
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+#[derive(Debug)]
+pub enum CheckpointError {
+    Io(std::io::Error),
+    Format(serde_json::Error),
+    Version(u32),
+}
+impl Display for CheckpointError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckpointError::Io(e) => write!(f, "checkpoint io: {}", e),
+            CheckpointError::Format(e) => write!(f, "checkpoint format: {}", e),
+            CheckpointError::Version(v) => write!(
+                f,
+                "checkpoint version {} is not supported (expected {})",
+                v, CHECKPOINT_VERSION
+            ),
+        }
+    }
+}
+impl std::error::Error for CheckpointError {}
+impl From<std::io::Error> for CheckpointError {
+    fn from(e: std::io::Error) -> Self {
+        CheckpointError::Io(e)
+    }
+}
+impl From<serde_json::Error> for CheckpointError {
+    fn from(e: serde_json::Error) -> Self {
+        CheckpointError::Format(e)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::task::{evaluate, Task};
+    use crate::tasks::xor::Xor;
+
+    fn generation(population: &mut Population) {
+        let fitnesses = (0..population.genomes.len())
+            .map(|i| evaluate(&Xor {}, population, i).fitness)
+            .collect::<Vec<_>>();
+        population.evolve(&fitnesses);
+    }
+    #[test]
+    fn resumed_run_matches_an_uninterrupted_one() {
+        let path =
+            std::env::temp_dir().join(format!("snake-neat-checkpoint-{}.json", std::process::id()));
+        let mut environment = Xor {}.environment();
+        environment.population_count = 30;
+        let mut population = Population::seeded(environment, 3);
+        generation(&mut population);
+        Checkpoint::new(population.clone(), None, vec![1.0], vec![0.5])
+            .save(&path)
+            .unwrap();
+        let loaded = Checkpoint::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.bests, vec![1.0]);
+        assert_eq!(loaded.averages, vec![0.5]);
+        let mut resumed = loaded.population;
+        generation(&mut population);
+        generation(&mut resumed);
+        assert_eq!(resumed.generation, 2);
+        assert_eq!(
+            serde_json::to_string(&resumed).unwrap(),
+            serde_json::to_string(&population).unwrap()
+        );
+    }
+    #[test]
+    fn other_versions_are_refused() {
+        let path = std::env::temp_dir().join(format!(
+            "snake-neat-checkpoint-version-{}.json",
+            std::process::id()
+        ));
+        let mut environment = Xor {}.environment();
+        environment.population_count = 5;
+        let mut checkpoint =
+            Checkpoint::new(Population::seeded(environment, 1), None, vec![], vec![]);
+        checkpoint.version = CHECKPOINT_VERSION + 1;
+        checkpoint.save(&path).unwrap();
+        let loaded = Checkpoint::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            loaded,
+            Err(CheckpointError::Version(v)) if v == CHECKPOINT_VERSION + 1
+        ));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::neat::genome::Genome;
+use crate::neat::node::Node;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CompatibilityFactors {
+    pub c1: f32,
+    pub c2: f32,
+    pub c3: f32,
+}
+
+#[derive(Default)]
+pub struct Compatibility {
+    pub excess: f32,
+    pub disjoint: f32,
+    pub weight_difference: f32,
+    pub n: f32,
+}
+
+impl Compatibility {
+    pub fn new() -> Self {
+        Self {
+            excess: 0.0,
+            disjoint: 0.0,
+            weight_difference: 0.0,
+            n: 0.0,
+        }
+    }
+    pub fn between(genome: &Genome, repr: &Genome) -> Self {
+        let mut compatibility = Compatibility::new();
+        let repr_innovation_max = repr
+            .connections
+            .iter()
+            .map(|c| c.innovation)
+            .max()
+            .unwrap_or_default();
+        let mut num_weights = 0.0;
+        for conn in genome.connections.iter() {
+            let matching = repr
+                .connections
+                .iter()
+                .find(|c| c.innovation == conn.innovation);
+            if let Some(matching) = matching {
+                compatibility.weight_difference += conn.weight - matching.weight;
+                num_weights += 1.0;
+            }
+            if conn.innovation > repr_innovation_max {
+                compatibility.excess += 1.0;
+            } else if matching.is_none() {
+                compatibility.disjoint += 1.0;
+            }
+        }
+        let repr_node_max = repr
+            .nodes
+            .iter()
+            .max_by(|a, b| a.id.cmp(&b.id))
+            .unwrap_or(&Node::new())
+            .id;
+        for node in genome.nodes.iter() {
+            if node.id > repr_node_max {
+                compatibility.excess += 1.0;
+            } else if !repr.nodes.iter().any(|n| n.id == node.id) {
+                compatibility.disjoint += 1.0;
+            }
+        }
+        let n = genome.connections.len().max(repr.connections.len());
+        compatibility.n = if n < 20 { 1.0 } else { n as f32 };
+        compatibility.weight_difference /= num_weights;
+        compatibility
+    }
+    pub fn distance(&self, factors: &CompatibilityFactors) -> f32 {
+        factors.c1 * self.excess / self.n
+            + factors.c2 * self.disjoint / self.n
+            + factors.c3 * self.weight_difference
+    }
+}
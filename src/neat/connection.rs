@@ -0,0 +1,21 @@
+use crate::neat::{Innovation, NodeId};
+use serde::{Deserialize, Serialize};
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Connection {
+    pub weight: f32,
+    pub innovation: Innovation,
+    pub enabled: bool,
+    pub from: NodeId,
+    pub to: NodeId,
+}
+impl Connection {
+    pub fn new(from: NodeId, to: NodeId, weight: f32, innovation: Innovation) -> Self {
+        Self {
+            from,
+            to,
+            weight,
+            enabled: true,
+            innovation,
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use crate::neat::compatibility::CompatibilityFactors;
+use crate::neat::connection::Connection;
+use crate::neat::genome::Genome;
+use crate::neat::innovation::ExistingInnovation;
+use crate::neat::node::{ActivationFunction, Node, NodeType};
+use crate::neat::task::Aggregation;
+use crate::neat::{Generation, GenomeId};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Environment {
+    pub population_count: i32,
+    pub input_size: usize,
+    pub output_size: usize,
+    pub compatibility_factors: CompatibilityFactors,
+    pub compatibility_threshold: f32,
+    pub stagnation_threshold: Generation,
+    pub only_mutate: f32,
+    pub elitism: f32,
+    pub crossover_only: f32,
+    pub inherit_disable: f32,
+    pub add_connection: f32,
+    pub connection_weight: f32,
+    pub perturb: f32,
+    pub add_node: f32,
+    pub max_turns: i32,
+    #[serde(default)]
+    pub recurrent: bool,
+    #[serde(default)]
+    pub feed_forward: bool,
+    // allowed node functions, the first one is given to new hidden nodes
+    #[serde(default = "ActivationFunction::defaults")]
+    pub activation_functions: Vec<ActivationFunction>,
+    #[serde(default)]
+    pub mutate_activation: f32,
+    // games played per genome, folded into one fitness by aggregation
+    #[serde(default = "Environment::default_trials")]
+    pub trials: i32,
+    #[serde(default)]
+    pub aggregation: Aggregation,
+    #[serde(default = "Environment::default_stddev_penalty")]
+    pub stddev_penalty: f32,
+    // one food sequence per generation (and trial) for every genome
+    #[serde(default)]
+    pub shared_food: bool,
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Environment {
+    pub fn new() -> Self {
+        Self {
+            population_count: 0,
+            input_size: 0,
+            output_size: 0,
+            compatibility_factors: CompatibilityFactors {
+                c1: 0.0,
+                c2: 0.0,
+                c3: 0.0,
+            },
+            compatibility_threshold: 0.0,
+            stagnation_threshold: 0,
+            only_mutate: 0.0,
+            elitism: 0.0,
+            crossover_only: 0.0,
+            inherit_disable: 0.0,
+            add_connection: 0.0,
+            connection_weight: 0.0,
+            perturb: 0.0,
+            add_node: 0.0,
+            max_turns: 0,
+            recurrent: false,
+            feed_forward: false,
+            activation_functions: ActivationFunction::defaults(),
+            mutate_activation: 0.0,
+            trials: Self::default_trials(),
+            aggregation: Aggregation::Mean,
+            stddev_penalty: Self::default_stddev_penalty(),
+            shared_food: false,
+        }
+    }
+    fn default_trials() -> i32 {
+        1
+    }
+    fn default_stddev_penalty() -> f32 {
+        1.0
+    }
+    // a fully connected starting genome whose outputs use the first allowed function
+    pub fn new_genome(&self, id: GenomeId, rng: &mut impl Rng) -> Genome {
+        let mut genome = Genome::new(id, self.input_size, self.output_size, rng);
+        for node in genome.nodes.iter_mut() {
+            if node.ty == NodeType::Output {
+                node.activation = self.activation_functions[0];
+            }
+        }
+        genome
+    }
+    pub fn mutate(
+        &self,
+        mut genome: Genome,
+        existing_innovation: &mut ExistingInnovation,
+        rng: &mut impl Rng,
+    ) -> Genome {
+        for conn in genome.connections.iter_mut() {
+            if rng.gen_range(0.0..1.0) < self.connection_weight {
+                if rng.gen_range(0.0..1.0) < self.perturb {
+                    let perturb = rng.gen_range(-1.0..1.0);
+                    conn.weight += perturb;
+                } else {
+                    conn.weight = rng.gen_range(0.0..1.0);
+                }
+            }
+        }
+        if self.activation_functions.len() > 1 && rng.gen_range(0.0..1.0) < self.mutate_activation {
+            let candidates = genome
+                .nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, n)| n.ty == NodeType::Hidden || n.ty == NodeType::Output)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            if !candidates.is_empty() {
+                let idx = candidates[rng.gen_range(0..candidates.len())];
+                genome.nodes[idx].activation =
+                    self.activation_functions[rng.gen_range(0..self.activation_functions.len())];
+            }
+        }
+        if rng.gen_range(0.0..1.0) < self.add_node {
+            if genome.connections.is_empty() {
+                return genome;
+            }
+            let idx = rng.gen_range(0..genome.connections.len());
+            let existing_connection = genome.connections.get(idx).cloned().unwrap();
+            let id = existing_innovation.split(existing_connection.innovation, &genome);
+            let mut new = Node::explicit(id, NodeType::Hidden);
+            new.activation = self.activation_functions[0];
+            genome.node_id_gen = genome.node_id_gen.max(id + 1);
+            genome.connections.get_mut(idx).unwrap().enabled = false;
+            let a = Connection::new(
+                existing_connection.from,
+                new.id,
+                1.0,
+                existing_innovation.check(existing_connection.from, new.id),
+            );
+            let b = Connection::new(
+                new.id,
+                existing_connection.to,
+                existing_connection.weight,
+                existing_innovation.check(new.id, existing_connection.to),
+            );
+            genome.connections.push(a);
+            genome.connections.push(b);
+            genome.nodes.push(new);
+        } else if rng.gen_range(0.0..1.0) < self.add_connection {
+            if let Some((input, output)) = self.select_connection_nodes(&genome, rng) {
+                let connection = Connection::new(
+                    input.id,
+                    output.id,
+                    rng.gen_range(0.0..1.0),
+                    existing_innovation.check(input.id, output.id),
+                );
+                genome.connections.push(connection);
+            }
+        }
+        genome
+    }
+    pub fn crossover(
+        &self,
+        id: GenomeId,
+        best: Genome,
+        other: Genome,
+        rng: &mut impl Rng,
+    ) -> Genome {
+        let mut child = self.new_genome(id, rng);
+        for conn in best.connections.iter() {
+            let mut gene = *conn;
+            let mut from_node = *best.nodes.iter().find(|n| n.id == gene.from).unwrap();
+            let mut to_node = *best.nodes.iter().find(|n| n.id == gene.to).unwrap();
+            if let Some(matching) = other
+                .connections
+                .iter()
+                .find(|c| c.innovation == conn.innovation)
+            {
+                if rng.gen_range(0.0..1.0) < 0.5 {
+                    gene = *matching;
+                    from_node = *other.nodes.iter().find(|n| n.id == gene.from).unwrap();
+                    to_node = *other.nodes.iter().find(|n| n.id == gene.to).unwrap();
+                }
+                if (!conn.enabled || !matching.enabled)
+                    && rng.gen_range(0.0..1.0) < self.inherit_disable
+                {
+                    gene.enabled = false;
+                }
+            }
+            for node in [from_node, to_node] {
+                if let Some(existing) = child.nodes.iter_mut().find(|n| n.id == node.id) {
+                    // outputs come from Genome::new, keep the inherited function
+                    existing.activation = node.activation;
+                } else {
+                    child.nodes.push(node);
+                }
+            }
+            if child
+                .connections
+                .iter()
+                .find(|c| c.from == gene.from && c.to == gene.to)
+                .is_none()
+                && !(self.feed_forward && child.creates_cycle(gene.from, gene.to))
+            {
+                child.connections.push(gene);
+            }
+        }
+        child.node_id_gen = best.node_id_gen.max(other.node_id_gen);
+        child
+    }
+    pub fn select_connection_nodes(
+        &self,
+        genome: &Genome,
+        rng: &mut impl Rng,
+    ) -> Option<(Node, Node)> {
+        let potential_inputs = genome
+            .nodes
+            .iter()
+            .filter(|n| n.ty != NodeType::Output)
+            .copied()
+            .collect::<Vec<_>>();
+        let potential_outputs = genome
+            .nodes
+            .iter()
+            .filter(|n| n.ty != NodeType::Input && n.ty != NodeType::Bias)
+            .copied()
+            .collect::<Vec<_>>();
+        if potential_inputs.is_empty() || potential_outputs.is_empty() {
+            return None;
+        }
+        let idx = rng.gen_range(0..potential_inputs.len());
+        let mut input = potential_inputs.get(idx).copied().unwrap();
+        let idx = rng.gen_range(0..potential_outputs.len());
+        let mut output = potential_outputs.get(idx).copied().unwrap();
+        while input.id == output.id && potential_inputs.len() > 1 {
+            let idx = rng.gen_range(0..potential_inputs.len());
+            input = potential_inputs.get(idx).copied().unwrap();
+        }
+        while input.id == output.id && potential_outputs.len() > 1 {
+            let idx = rng.gen_range(0..potential_outputs.len());
+            output = potential_outputs.get(idx).copied().unwrap();
+        }
+        if input.id == output.id {
+            return None;
+        }
+        if genome
+            .connections
+            .iter()
+            .find(|c| c.from == input.id && c.to == output.id)
+            .is_some()
+        {
+            return None;
+        }
+        if self.feed_forward && genome.creates_cycle(input.id, output.id) {
+            return None;
+        }
+        Some((input, output))
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::genome::tests::{environment, genome};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn new_genomes_start_with_the_first_allowed_function() {
+        let mut environment = environment();
+        environment.activation_functions = vec![ActivationFunction::Tanh];
+        let genome = environment.new_genome(0, &mut ChaCha8Rng::seed_from_u64(0));
+        let output = genome.nodes.iter().find(|n| n.ty == NodeType::Output);
+        assert_eq!(output.unwrap().activation, ActivationFunction::Tanh);
+    }
+    #[test]
+    fn activation_mutation_stays_in_the_allow_list() {
+        let mut environment = environment();
+        environment.activation_functions = vec![ActivationFunction::Relu, ActivationFunction::Abs];
+        environment.connection_weight = 0.0;
+        environment.mutate_activation = 1.0;
+        environment.add_node = 0.0;
+        environment.add_connection = 0.0;
+        let mut existing_innovation = ExistingInnovation::new(2, 1);
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let mut mutated = genome();
+        for _ in 0..20 {
+            mutated = environment.mutate(mutated, &mut existing_innovation, &mut rng);
+        }
+        // both the output and the hidden node were picked at some point
+        for id in [2, 4] {
+            let node = mutated.nodes.iter().find(|n| n.id == id).unwrap();
+            assert!(environment.activation_functions.contains(&node.activation));
+        }
+        // inputs and biases are never activated, so never mutated
+        assert!(mutated
+            .nodes
+            .iter()
+            .filter(|n| n.ty == NodeType::Input || n.ty == NodeType::Bias)
+            .all(|n| n.activation == ActivationFunction::Sigmoid));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::neat::connection::Connection;
+use crate::neat::environment::Environment;
+use crate::neat::node::{Node, NodeType};
+use crate::neat::{Depth, GenomeId, NodeId, SpeciesId};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Genome {
+    pub id: GenomeId,
+    pub nodes: Vec<Node>,
+    pub connections: Vec<Connection>,
+    pub depth: Depth,
+    pub species: SpeciesId,
+    pub node_id_gen: NodeId,
+}
+impl Genome {
+    pub fn new(id: GenomeId, input_size: usize, output_size: usize, rng: &mut impl Rng) -> Self {
+        // setup nodes + connections
+        if input_size == 0 || output_size == 0 {
+            panic!("Genome has no input/output dimensions");
+        }
+        let mut nodes = Vec::new();
+        let mut connections = Vec::new();
+        for input in 0..input_size {
+            nodes.push(Node::explicit(input, NodeType::Input));
+        }
+        for output in input_size..input_size + output_size {
+            nodes.push(Node::explicit(output, NodeType::Output));
+        }
+        for bias in input_size + output_size..input_size + output_size * 2 {
+            nodes.push(Node::explicit(bias, NodeType::Bias));
+        }
+        let node_id_gen = nodes.len();
+        let mut innovation = 0;
+        for i in 0..input_size {
+            for o in input_size..input_size + output_size {
+                let connection = Connection::new(i, o, rng.gen_range(0.0..1.0), innovation);
+                connections.push(connection);
+                innovation += 1;
+            }
+        }
+        for bias in input_size + output_size..input_size + output_size * 2 {
+            for o in input_size..input_size + output_size {
+                let connection = Connection::new(bias, o, rng.gen_range(0.0..1.0), innovation);
+                connections.push(connection);
+                innovation += 1;
+            }
+        }
+        Self {
+            id,
+            nodes,
+            connections,
+            depth: 1,
+            species: 0,
+            node_id_gen,
+        }
+    }
+    pub fn depth(&self, count: i32, to: NodeId) -> (i32, bool) {
+        let mut max = count;
+        if count > 100 {
+            return (10, true);
+        }
+        for c in self.connections.iter() {
+            if c.to == to {
+                let (current, aborted) = self.depth(count + 1, c.from);
+                if aborted {
+                    return (current, true);
+                }
+                if current > max {
+                    max = current;
+                }
+            }
+        }
+        (max, false)
+    }
+    // true when from -> to would close a loop, disabled connections count since crossover can revive them
+    pub fn creates_cycle(&self, from: NodeId, to: NodeId) -> bool {
+        let mut stack = vec![to];
+        let mut seen = vec![];
+        while let Some(current) = stack.pop() {
+            if current == from {
+                return true;
+            }
+            if seen.contains(&current) {
+                continue;
+            }
+            seen.push(current);
+            for c in self.connections.iter().filter(|c| c.from == current) {
+                stack.push(c.to);
+            }
+        }
+        false
+    }
+    // longest path to `to`; visiting holds the path being walked so a cycle
+    // is skipped instead of recursing forever
+    pub fn exact_depth(
+        &self,
+        to: NodeId,
+        memo: &mut HashMap<NodeId, Depth>,
+        visiting: &mut HashSet<NodeId>,
+    ) -> Depth {
+        if let Some(depth) = memo.get(&to) {
+            return *depth;
+        }
+        visiting.insert(to);
+        let mut max = 0;
+        for c in self.connections.iter().filter(|c| c.to == to) {
+            if !visiting.contains(&c.from) {
+                max = max.max(self.exact_depth(c.from, memo, visiting) + 1);
+            }
+        }
+        visiting.remove(&to);
+        memo.insert(to, max);
+        max
+    }
+    pub fn max_depth(&self, environment: &Environment) -> Depth {
+        let outputs = environment.input_size..(environment.input_size + environment.output_size);
+        if environment.feed_forward {
+            let mut memo = HashMap::new();
+            let mut visiting = HashSet::new();
+            return outputs
+                .map(|o| self.exact_depth(o, &mut memo, &mut visiting))
+                .max()
+                .unwrap_or_default();
+        }
+        let mut max = 0;
+        for o in outputs {
+            let (current, _aborted) = self.depth(0, o);
+            if current > max {
+                max = current;
+            }
+        }
+        max
+    }
+}
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    // inputs 0 + 1, output 2, bias 3 and hidden 4 between input 0 and the output
+    pub(crate) fn genome() -> Genome {
+        let mut genome = Genome::new(0, 2, 1, &mut ChaCha8Rng::seed_from_u64(0));
+        genome.nodes.push(Node::explicit(4, NodeType::Hidden));
+        genome.node_id_gen = 5;
+        genome.connections.push(Connection::new(0, 4, 1.0, 3));
+        genome.connections.push(Connection::new(4, 2, 1.0, 4));
+        genome
+    }
+    pub(crate) fn environment() -> Environment {
+        let mut environment = Environment::new();
+        environment.input_size = 2;
+        environment.output_size = 1;
+        environment.feed_forward = true;
+        environment
+    }
+    #[test]
+    fn creates_cycle_follows_paths() {
+        let genome = genome();
+        assert!(genome.creates_cycle(2, 4));
+        assert!(genome.creates_cycle(2, 0));
+        assert!(genome.creates_cycle(4, 4));
+        assert!(!genome.creates_cycle(1, 4));
+        assert!(!genome.creates_cycle(0, 2));
+    }
+    #[test]
+    fn creates_cycle_counts_disabled_connections() {
+        let mut genome = genome();
+        genome
+            .connections
+            .iter_mut()
+            .for_each(|c| c.enabled = false);
+        assert!(genome.creates_cycle(2, 0));
+    }
+    #[test]
+    fn max_depth_is_the_longest_path() {
+        assert_eq!(genome().max_depth(&environment()), 2);
+    }
+    #[test]
+    fn max_depth_terminates_on_a_cycle() {
+        let mut genome = genome();
+        genome.connections.push(Connection::new(2, 4, 1.0, 5));
+        assert_eq!(genome.max_depth(&environment()), 2);
+    }
+}
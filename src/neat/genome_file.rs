@@ -0,0 +1,246 @@
+use crate::neat::environment::Environment;
+use crate::neat::genome::Genome;
+use crate::neat::node::NodeType;
+use crate::neat::NodeId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+pub const GENOME_FILE_VERSION: u32 = 1;
+// Network::compile allocates a slot per id, a node_id_gen past this is treated as a corrupt file
+pub const MAX_NODE_ID: NodeId = 1 << 24;
+
+// json envelope for a single genome, sizes are repeated so a file can be checked before use
+#[derive(Serialize, Deserialize)]
+pub struct GenomeFile {
+    pub version: u32,
+    pub input_size: usize,
+    pub output_size: usize,
+    pub genome: Genome,
+}
+impl GenomeFile {
+    pub fn new(genome: Genome, environment: &Environment) -> Self {
+        Self {
+            version: GENOME_FILE_VERSION,
+            input_size: environment.input_size,
+            output_size: environment.output_size,
+            genome,
+        }
+    }
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), GenomeFileError> {
+        std::fs::write(path, self.to_json())?;
+        Ok(())
+    }
+    pub fn load(
+        path: impl AsRef<Path>,
+        environment: &Environment,
+    ) -> Result<Genome, GenomeFileError> {
+        let file = serde_json::from_str::<Self>(&std::fs::read_to_string(path)?)?;
+        if file.version != GENOME_FILE_VERSION {
+            return Err(GenomeFileError::Version(file.version));
+        }
+        file.validate(environment)?;
+        Ok(file.genome)
+    }
+    pub fn validate(&self, environment: &Environment) -> Result<(), GenomeFileError> {
+        if self.input_size != environment.input_size || self.output_size != environment.output_size
+        {
+            return Err(GenomeFileError::Invalid(format!(
+                "genome is {}x{} but the environment expects {}x{}",
+                self.input_size, self.output_size, environment.input_size, environment.output_size
+            )));
+        }
+        let genome = &self.genome;
+        let count = |ty: NodeType| genome.nodes.iter().filter(|n| n.ty == ty).count();
+        if count(NodeType::Input) != self.input_size
+            || count(NodeType::Output) != self.output_size
+            || count(NodeType::Bias) != self.output_size
+        {
+            return Err(GenomeFileError::Invalid(
+                "node types do not match the declared sizes".to_string(),
+            ));
+        }
+        if genome.node_id_gen > MAX_NODE_ID {
+            return Err(GenomeFileError::Invalid(format!(
+                "node_id_gen {} is past the limit of {}",
+                genome.node_id_gen, MAX_NODE_ID
+            )));
+        }
+        let mut ids = HashSet::new();
+        // inputs, outputs and biases keep the ids Genome::new gives them
+        for node in genome.nodes.iter() {
+            if !ids.insert(node.id) {
+                return Err(GenomeFileError::Invalid(format!(
+                    "node {} appears more than once",
+                    node.id
+                )));
+            }
+            if node.id >= genome.node_id_gen {
+                return Err(GenomeFileError::Invalid(format!(
+                    "node {} is not below node_id_gen {}",
+                    node.id, genome.node_id_gen
+                )));
+            }
+            let expected = match node.ty {
+                NodeType::Input => node.id < self.input_size,
+                NodeType::Output => {
+                    (self.input_size..self.input_size + self.output_size).contains(&node.id)
+                }
+                NodeType::Bias => (self.input_size + self.output_size
+                    ..self.input_size + self.output_size * 2)
+                    .contains(&node.id),
+                NodeType::Hidden => node.id >= self.input_size + self.output_size * 2,
+            };
+            if !expected {
+                return Err(GenomeFileError::Invalid(format!(
+                    "node {} has an unexpected id for {:?}",
+                    node.id, node.ty
+                )));
+            }
+        }
+        if let Some(node) = genome.nodes.iter().find(|n| {
+            (n.ty == NodeType::Hidden || n.ty == NodeType::Output)
+                && !environment.activation_functions.contains(&n.activation)
+        }) {
+            return Err(GenomeFileError::Invalid(format!(
+                "node {} uses {:?} which is not in activation_functions",
+                node.id, node.activation
+            )));
+        }
+        for conn in genome.connections.iter() {
+            for id in [conn.from, conn.to] {
+                if !genome.nodes.iter().any(|n| n.id == id) {
+                    return Err(GenomeFileError::Invalid(format!(
+                        "connection {} references missing node {}",
+                        conn.innovation, id
+                    )));
+                }
+            }
+        }
+        if environment.feed_forward {
+            if let Some(conn) = genome
+                .connections
+                .iter()
+                .find(|c| c.enabled && genome.creates_cycle(c.from, c.to))
+            {
+                return Err(GenomeFileError::Invalid(format!(
+                    "connection {} ({} -> {}) closes a cycle but the environment is feed forward",
+                    conn.innovation, conn.from, conn.to
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+#[derive(Debug)]
+pub enum GenomeFileError {
+    Io(std::io::Error),
+    Format(serde_json::Error),
+    Version(u32),
+    Invalid(String),
+}
+impl Display for GenomeFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GenomeFileError::Io(e) => write!(f, "genome io: {}", e),
+            GenomeFileError::Format(e) => write!(f, "genome format: {}", e),
+            GenomeFileError::Version(v) => write!(
+                f,
+                "genome file version {} is not supported (expected {})",
+                v, GENOME_FILE_VERSION
+            ),
+            GenomeFileError::Invalid(reason) => write!(f, "invalid genome: {}", reason),
+        }
+    }
+}
+impl std::error::Error for GenomeFileError {}
+impl From<std::io::Error> for GenomeFileError {
+    fn from(e: std::io::Error) -> Self {
+        GenomeFileError::Io(e)
+    }
+}
+impl From<serde_json::Error> for GenomeFileError {
+    fn from(e: serde_json::Error) -> Self {
+        GenomeFileError::Format(e)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::connection::Connection;
+    use crate::neat::genome::tests::{environment, genome};
+    use crate::neat::node::{ActivationFunction, Node};
+
+    #[test]
+    fn validate_accepts_an_acyclic_genome() {
+        let environment = environment();
+        assert!(GenomeFile::new(genome(), &environment)
+            .validate(&environment)
+            .is_ok());
+    }
+    #[test]
+    fn validate_rejects_a_cycle_when_feed_forward() {
+        let mut environment = environment();
+        let mut genome = genome();
+        genome.connections.push(Connection::new(2, 4, 1.0, 5));
+        let file = GenomeFile::new(genome, &environment);
+        assert!(matches!(
+            file.validate(&environment),
+            Err(GenomeFileError::Invalid(_))
+        ));
+        environment.feed_forward = false;
+        assert!(file.validate(&environment).is_ok());
+    }
+    #[test]
+    fn validate_rejects_activations_outside_the_allow_list() {
+        let mut environment = environment();
+        let mut genome = genome();
+        genome.nodes[4].activation = ActivationFunction::Tanh;
+        let file = GenomeFile::new(genome, &environment);
+        assert!(matches!(
+            file.validate(&environment),
+            Err(GenomeFileError::Invalid(_))
+        ));
+        environment.activation_functions =
+            vec![ActivationFunction::Sigmoid, ActivationFunction::Tanh];
+        assert!(file.validate(&environment).is_ok());
+    }
+    #[test]
+    fn validate_rejects_duplicate_and_out_of_range_ids() {
+        let environment = environment();
+        let invalid = |genome: Genome| {
+            matches!(
+                GenomeFile::new(genome, &environment).validate(&environment),
+                Err(GenomeFileError::Invalid(_))
+            )
+        };
+        let mut duplicate = genome();
+        duplicate.nodes.push(Node::explicit(4, NodeType::Hidden));
+        assert!(invalid(duplicate));
+        let mut past_gen = genome();
+        past_gen.nodes.push(Node::explicit(5, NodeType::Hidden));
+        assert!(invalid(past_gen));
+        let mut huge = genome();
+        huge.nodes
+            .push(Node::explicit(usize::MAX - 1, NodeType::Hidden));
+        huge.node_id_gen = usize::MAX;
+        assert!(invalid(huge));
+    }
+    #[test]
+    fn save_and_load_round_trip() {
+        let environment = environment();
+        let path =
+            std::env::temp_dir().join(format!("snake-neat-genome-{}.json", std::process::id()));
+        GenomeFile::new(genome(), &environment).save(&path).unwrap();
+        let loaded = GenomeFile::load(&path, &environment);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(
+            serde_json::to_string(&loaded.unwrap()).unwrap(),
+            serde_json::to_string(&genome()).unwrap()
+        );
+    }
+}
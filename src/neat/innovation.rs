@@ -0,0 +1,133 @@
+use crate::neat::genome::Genome;
+use crate::neat::{Innovation, NodeId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ExistingInnovation {
+    #[serde(with = "pairs")]
+    pub existing: HashMap<(NodeId, NodeId), Innovation>,
+    pub generator: Innovation,
+    // hidden node created by splitting the connection with this innovation
+    pub splits: HashMap<Innovation, NodeId>,
+    pub node_id_gen: NodeId,
+}
+
+impl ExistingInnovation {
+    pub fn check(&mut self, from: NodeId, to: NodeId) -> Innovation {
+        let pair = (from, to);
+        if let Some(k) = self.existing.get(&pair) {
+            *k
+        } else {
+            self.generator += 1;
+            let idx = self.generator;
+            self.existing.insert(pair, idx);
+            idx
+        }
+    }
+    // same split in any genome gets the same node, a genome splitting it again gets a fresh one
+    pub fn split(&mut self, innovation: Innovation, genome: &Genome) -> NodeId {
+        if let Some(id) = self.splits.get(&innovation) {
+            if !genome.nodes.iter().any(|n| n.id == *id) {
+                return *id;
+            }
+        }
+        let id = self.node_id_gen;
+        self.node_id_gen += 1;
+        self.splits.entry(innovation).or_insert(id);
+        id
+    }
+    pub fn absorb(&mut self, genome: &Genome) {
+        for conn in genome.connections.iter() {
+            self.existing.insert((conn.from, conn.to), conn.innovation);
+            self.generator = self.generator.max(conn.innovation);
+        }
+        let next_id = genome
+            .nodes
+            .iter()
+            .map(|n| n.id + 1)
+            .max()
+            .unwrap_or_default();
+        self.node_id_gen = self.node_id_gen.max(next_id);
+    }
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        let mut generator = 0;
+        let mut existing = HashMap::new();
+        // fully-connected innovations
+        for i in 0..inputs {
+            for o in inputs..(inputs + outputs) {
+                existing.insert((i, o), generator);
+                generator += 1;
+            }
+        }
+        for i in (inputs + outputs)..(inputs + outputs * 2) {
+            for o in inputs..(inputs + outputs) {
+                existing.insert((i, o), generator);
+                generator += 1;
+            }
+        }
+        Self {
+            existing,
+            generator,
+            splits: HashMap::new(),
+            node_id_gen: inputs + outputs * 2,
+        }
+    }
+}
+// json maps need string keys, so the (from, to) pairs are stored as a list
+mod pairs {
+    use crate::neat::{Innovation, NodeId};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::HashMap;
+
+    pub(super) fn serialize<S: Serializer>(
+        existing: &HashMap<(NodeId, NodeId), Innovation>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut pairs = existing.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>();
+        pairs.sort_by_key(|(_, v)| *v);
+        pairs.serialize(serializer)
+    }
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<(NodeId, NodeId), Innovation>, D::Error> {
+        let pairs = Vec::<((NodeId, NodeId), Innovation)>::deserialize(deserializer)?;
+        Ok(pairs.into_iter().collect())
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::genome::tests::genome;
+    use crate::neat::node::{Node, NodeType};
+
+    #[test]
+    fn split_shares_the_node_across_genomes() {
+        let mut existing = ExistingInnovation::new(2, 1);
+        let genome = genome();
+        existing.absorb(&genome);
+        let first = existing.split(0, &genome);
+        assert_eq!(first, 5);
+        assert_eq!(existing.split(0, &genome), first);
+        assert_ne!(existing.split(1, &genome), first);
+    }
+    #[test]
+    fn split_again_in_the_same_genome_gets_a_fresh_node() {
+        let mut existing = ExistingInnovation::new(2, 1);
+        let mut genome = genome();
+        existing.absorb(&genome);
+        let first = existing.split(0, &genome);
+        genome.nodes.push(Node::explicit(first, NodeType::Hidden));
+        let second = existing.split(0, &genome);
+        assert_ne!(second, first);
+        // the registry keeps pointing other genomes at the first node
+        assert_eq!(existing.splits[&0], first);
+    }
+    #[test]
+    fn absorb_moves_ids_past_the_genome() {
+        let mut existing = ExistingInnovation::new(2, 1);
+        existing.absorb(&genome());
+        assert_eq!(existing.node_id_gen, 5);
+        assert_eq!(existing.check(0, 4), 3);
+        assert_eq!(existing.split(2, &genome()), 5);
+    }
+}
@@ -0,0 +1,24 @@
+pub mod checkpoint;
+pub mod compatibility;
+pub mod connection;
+pub mod environment;
+pub mod genome;
+pub mod genome_file;
+pub mod innovation;
+pub mod network;
+pub mod node;
+pub mod population;
+pub mod reproduction;
+pub mod species;
+pub mod stats;
+pub mod task;
+
+pub type NodeId = usize;
+pub type GenomeId = usize;
+pub type SpeciesId = usize;
+pub type Generation = i32;
+pub type Innovation = i32;
+pub type Depth = i32;
+pub type Fitness = f32;
+// stream cipher rng so a (seed, stream) pair replays exactly
+pub type GameRng = rand_chacha::ChaCha8Rng;
@@ -0,0 +1,212 @@
+use crate::neat::environment::Environment;
+use crate::neat::genome::Genome;
+use crate::neat::node::{ActivationFunction, NodeType};
+use crate::neat::NodeId;
+
+// a genome flattened for evaluation: enabled edges only, grouped by target in topological order.
+// edges that close a cycle read the value the source had before this pass, which in recurrent
+// mode is the previous tick (one-step delay) and otherwise zero.
+#[derive(Clone)]
+pub struct Network {
+    pub input_size: usize,
+    pub output_size: usize,
+    pub recurrent: bool,
+    size: usize,
+    order: Vec<NodeId>,
+    functions: Vec<ActivationFunction>,
+    offsets: Vec<usize>,
+    sources: Vec<NodeId>,
+    weights: Vec<f32>,
+}
+impl Network {
+    pub fn compile(genome: &Genome, environment: &Environment) -> Self {
+        let size = genome
+            .nodes
+            .iter()
+            .map(|n| n.id + 1)
+            .max()
+            .unwrap_or_default()
+            .max(environment.input_size + environment.output_size * 2);
+        let mut incoming = vec![vec![]; size];
+        for c in genome.connections.iter().filter(|c| c.enabled) {
+            incoming[c.to].push((c.from, c.weight));
+        }
+        let mut leaf = vec![false; size];
+        let mut activation = vec![ActivationFunction::Sigmoid; size];
+        for n in genome.nodes.iter() {
+            leaf[n.id] = n.ty == NodeType::Input || n.ty == NodeType::Bias;
+            activation[n.id] = n.activation;
+        }
+        let mut visited = vec![false; size];
+        let mut order = vec![];
+        for o in environment.input_size..environment.input_size + environment.output_size {
+            Self::visit(o, &incoming, &leaf, &mut visited, &mut order);
+        }
+        let mut offsets = vec![0];
+        let mut sources = vec![];
+        let mut weights = vec![];
+        for id in order.iter() {
+            for (from, weight) in incoming[*id].iter() {
+                sources.push(*from);
+                weights.push(*weight);
+            }
+            offsets.push(sources.len());
+        }
+        let functions = order.iter().map(|id| activation[*id]).collect();
+        Self {
+            input_size: environment.input_size,
+            output_size: environment.output_size,
+            recurrent: environment.recurrent,
+            size,
+            order,
+            functions,
+            offsets,
+            sources,
+            weights,
+        }
+    }
+    fn visit(
+        id: NodeId,
+        incoming: &[Vec<(NodeId, f32)>],
+        leaf: &[bool],
+        visited: &mut [bool],
+        order: &mut Vec<NodeId>,
+    ) {
+        // marking before recursing turns any edge back into the current path into a delayed read
+        if visited[id] || leaf[id] {
+            return;
+        }
+        visited[id] = true;
+        for (from, _) in incoming[id].iter() {
+            Self::visit(*from, incoming, leaf, visited, order);
+        }
+        order.push(id);
+    }
+    pub fn activations(&self) -> Activations {
+        Activations::new(self.size)
+    }
+    pub fn activate(&self, activations: &mut Activations, input: &[f32]) {
+        if !self.recurrent {
+            activations.reset();
+        }
+        let values = &mut activations.values;
+        values[..self.input_size].copy_from_slice(&input[..self.input_size]);
+        let bias = self.input_size + self.output_size;
+        for v in values[bias..bias + self.output_size].iter_mut() {
+            *v = 1.0;
+        }
+        for (i, id) in self.order.iter().enumerate() {
+            let mut sum = 0.0;
+            for e in self.offsets[i]..self.offsets[i + 1] {
+                sum += values[self.sources[e]] * self.weights[e];
+            }
+            values[*id] = self.functions[i].apply(sum);
+        }
+    }
+}
+#[derive(Clone, Default)]
+pub struct Activations {
+    pub values: Vec<f32>,
+}
+impl Activations {
+    pub fn new(size: usize) -> Self {
+        Self {
+            values: vec![0.0; size],
+        }
+    }
+    pub fn reset(&mut self) {
+        self.values.fill(0.0);
+    }
+    pub fn outputs(&self, environment: &Environment) -> &[f32] {
+        &self.values[environment.input_size..environment.input_size + environment.output_size]
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neat::connection::Connection;
+    use crate::neat::genome::tests::{environment, genome};
+
+    // the genome fixture with only 0 -> 4 -> 2 carrying weight
+    fn chain() -> Genome {
+        let mut genome = genome();
+        for c in genome.connections.iter_mut() {
+            c.weight = if c.innovation >= 3 { 1.0 } else { 0.0 };
+        }
+        genome
+    }
+    #[test]
+    fn each_node_applies_its_own_function() {
+        let environment = environment();
+        let mut genome = chain();
+        genome.nodes[2].activation = ActivationFunction::Identity;
+        genome.nodes[4].activation = ActivationFunction::Relu;
+        let network = Network::compile(&genome, &environment);
+        let mut activations = network.activations();
+        network.activate(&mut activations, &[2.0, 0.0]);
+        assert_eq!(activations.outputs(&environment), [2.0]);
+        network.activate(&mut activations, &[-2.0, 0.0]);
+        assert_eq!(activations.outputs(&environment), [0.0]);
+    }
+    #[test]
+    fn chain_is_evaluated_sources_first() {
+        let environment = environment();
+        let mut genome = chain();
+        genome.nodes[2].activation = ActivationFunction::Identity;
+        genome.nodes[4].activation = ActivationFunction::Identity;
+        genome.connections[3].weight = 2.0;
+        genome.connections[4].weight = 3.0;
+        let network = Network::compile(&genome, &environment);
+        assert_eq!(network.order, vec![4, 2]);
+        let mut activations = network.activations();
+        network.activate(&mut activations, &[1.5, 7.0]);
+        assert_eq!(activations.values[4], 3.0);
+        assert_eq!(activations.outputs(&environment), [9.0]);
+    }
+    #[test]
+    fn disabled_edges_are_left_out() {
+        let environment = environment();
+        let mut genome = chain();
+        genome.connections[4].enabled = false;
+        let network = Network::compile(&genome, &environment);
+        // nothing enabled reaches 4 from the output, so it is never evaluated
+        assert_eq!(network.order, vec![2]);
+        assert!(!network.sources.contains(&4));
+    }
+    // the chain with an extra 2 -> 4 edge closing a cycle, identity everywhere
+    fn cycle() -> Genome {
+        let mut genome = chain();
+        genome.connections.push(Connection::new(2, 4, 1.0, 5));
+        genome.nodes[2].activation = ActivationFunction::Identity;
+        genome.nodes[4].activation = ActivationFunction::Identity;
+        genome
+    }
+    #[test]
+    fn back_edges_read_the_previous_tick_when_recurrent() {
+        let mut environment = environment();
+        environment.feed_forward = false;
+        environment.recurrent = true;
+        let network = Network::compile(&cycle(), &environment);
+        assert_eq!(network.order, vec![4, 2]);
+        let mut activations = network.activations();
+        network.activate(&mut activations, &[1.0, 0.0]);
+        assert_eq!(activations.outputs(&environment), [1.0]);
+        // 4 now adds the output of the first pass
+        network.activate(&mut activations, &[1.0, 0.0]);
+        assert_eq!(activations.outputs(&environment), [2.0]);
+        network.activate(&mut activations, &[1.0, 0.0]);
+        assert_eq!(activations.outputs(&environment), [3.0]);
+    }
+    #[test]
+    fn values_reset_every_pass_unless_recurrent() {
+        let mut environment = environment();
+        environment.feed_forward = false;
+        environment.recurrent = false;
+        let network = Network::compile(&cycle(), &environment);
+        let mut activations = network.activations();
+        for _ in 0..3 {
+            network.activate(&mut activations, &[1.0, 0.0]);
+            assert_eq!(activations.outputs(&environment), [1.0]);
+        }
+    }
+}
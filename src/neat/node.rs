@@ -0,0 +1,80 @@
+use crate::neat::NodeId;
+use serde::{Deserialize, Serialize};
+#[derive(Copy, Clone, Serialize, Deserialize)]
+pub struct Node {
+    pub id: NodeId,
+    pub ty: NodeType,
+    #[serde(default)]
+    pub activation: ActivationFunction,
+}
+
+impl Node {
+    pub fn new() -> Self {
+        Self {
+            id: 0,
+            ty: NodeType::Hidden,
+            activation: ActivationFunction::Sigmoid,
+        }
+    }
+    pub fn explicit(id: NodeId, ty: NodeType) -> Self {
+        Self {
+            id,
+            ty,
+            activation: ActivationFunction::Sigmoid,
+        }
+    }
+}
+impl Default for Node {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum NodeType {
+    Input,
+    Output,
+    Bias,
+    Hidden,
+}
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum ActivationFunction {
+    #[default]
+    Sigmoid,
+    Tanh,
+    Relu,
+    Gaussian,
+    Sine,
+    Identity,
+    Step,
+    Abs,
+}
+impl ActivationFunction {
+    pub const ALL: [ActivationFunction; 8] = [
+        ActivationFunction::Sigmoid,
+        ActivationFunction::Tanh,
+        ActivationFunction::Relu,
+        ActivationFunction::Gaussian,
+        ActivationFunction::Sine,
+        ActivationFunction::Identity,
+        ActivationFunction::Step,
+        ActivationFunction::Abs,
+    ];
+    // steepened sigmoid from the original NEAT paper
+    pub const SIGMOID_SCALE: f32 = 4.9;
+    pub fn apply(&self, z: f32) -> f32 {
+        match self {
+            ActivationFunction::Sigmoid => 1.0 / (1.0 + (-Self::SIGMOID_SCALE * z).exp()),
+            ActivationFunction::Tanh => z.tanh(),
+            ActivationFunction::Relu => z.max(0.0),
+            ActivationFunction::Gaussian => (-z * z).exp(),
+            ActivationFunction::Sine => z.sin(),
+            ActivationFunction::Identity => z,
+            ActivationFunction::Step => f32::from(z > 0.0),
+            ActivationFunction::Abs => z.abs(),
+        }
+    }
+    pub fn defaults() -> Vec<ActivationFunction> {
+        vec![ActivationFunction::Sigmoid]
+    }
+}
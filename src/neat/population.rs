@@ -0,0 +1,102 @@
+use crate::neat::environment::Environment;
+use crate::neat::genome::Genome;
+use crate::neat::innovation::ExistingInnovation;
+use crate::neat::reproduction::reproduce;
+use crate::neat::species::{speciate, Species};
+use crate::neat::{Fitness, GameRng, Generation, SpeciesId};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Population {
+    pub environment: Environment,
+    pub genomes: Vec<Genome>,
+    pub species: Vec<Species>,
+    pub existing_innovation: ExistingInnovation,
+    pub generation: Generation,
+    pub species_id_gen: SpeciesId,
+    pub seed: u64,
+    // evolution stream, games draw from their own streams of the same seed
+    pub rng: ChaCha8Rng,
+}
+impl Population {
+    pub fn new(environment: Environment) -> Self {
+        Self::seeded(environment, rand::thread_rng().gen())
+    }
+    pub fn seeded(environment: Environment, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let genomes = (0..environment.population_count as usize)
+            .map(|id| environment.new_genome(id, &mut rng))
+            .collect();
+        let existing_innovation =
+            ExistingInnovation::new(environment.input_size, environment.output_size);
+        let mut population = Self {
+            environment,
+            genomes,
+            species: vec![],
+            existing_innovation,
+            generation: 0,
+            species_id_gen: 0,
+            seed,
+            rng,
+        };
+        population.speciate();
+        population
+    }
+    // every member starts as a copy of genome, mutation spreads them out from there
+    pub fn from_genome(environment: Environment, genome: &Genome, seed: u64) -> Self {
+        let mut population = Self::seeded(environment, seed);
+        population.existing_innovation.absorb(genome);
+        for (id, slot) in population.genomes.iter_mut().enumerate() {
+            *slot = genome.clone();
+            slot.id = id;
+            slot.depth = slot.max_depth(&population.environment);
+        }
+        population.species.clear();
+        population.speciate();
+        population
+    }
+    // independent of evaluation order so games can run in parallel and still replay,
+    // trial 0 keeps the stream single trial runs always used
+    pub fn game_rng(&self, index: usize, trial: i32) -> GameRng {
+        // shared food gives every genome of the generation the same stream
+        let index = if self.environment.shared_food {
+            0
+        } else {
+            index
+        };
+        let mut rng = GameRng::seed_from_u64(self.seed);
+        rng.set_stream(((trial as u64) << 56 | (self.generation as u64) << 32 | index as u64) + 1);
+        rng
+    }
+    pub fn speciate(&mut self) {
+        speciate(
+            &mut self.species,
+            &mut self.species_id_gen,
+            &mut self.genomes,
+            self.generation,
+            &self.environment,
+            &mut self.rng,
+        );
+    }
+    // fitnesses are indexed like genomes; returns the average fitness of the evaluated generation
+    pub fn evolve(&mut self, fitnesses: &[Fitness]) -> Fitness {
+        let (next_gen, average) = reproduce(
+            &mut self.species,
+            &self.genomes,
+            fitnesses,
+            self.generation,
+            &self.environment,
+            &mut self.existing_innovation,
+            &mut self.rng,
+        );
+        self.genomes = next_gen;
+        self.generation += 1;
+        for genome in self.genomes.iter_mut() {
+            genome.depth = genome.max_depth(&self.environment);
+        }
+        self.speciate();
+        average
+    }
+}
@@ -0,0 +1,151 @@
+use crate::neat::environment::Environment;
+use crate::neat::genome::Genome;
+use crate::neat::innovation::ExistingInnovation;
+use crate::neat::species::Species;
+use crate::neat::{Fitness, Generation};
+use rand::Rng;
+
+pub fn reproduce(
+    species: &mut Vec<Species>,
+    population: &[Genome],
+    fitnesses: &[Fitness],
+    generation: Generation,
+    environment: &Environment,
+    existing_innovation: &mut ExistingInnovation,
+    rng: &mut impl Rng,
+) -> (Vec<Genome>, Fitness) {
+    // species %
+    let mut to_cull = vec![];
+    for s in species.iter_mut() {
+        let max = s
+            .members
+            .iter()
+            .map(|m| fitnesses[*m])
+            .max_by(|a, b| a.partial_cmp(b).unwrap())
+            .unwrap_or_default();
+        if max > s.max_fitness {
+            s.max_fitness = max;
+            s.last_improved = generation;
+        }
+        if generation > s.last_improved + environment.stagnation_threshold {
+            to_cull.push(s.id);
+        }
+    }
+    // cull
+    for id in to_cull {
+        if species.len() == 1 {
+            // replace all with starting genomes
+            // put all into starting species
+            // for now continuing
+            continue;
+        }
+        species.retain(|s| s.id != id);
+    }
+    let mut total = 0.0;
+    for s in species.iter_mut() {
+        s.shared_fitness = 0.0;
+        if s.members.is_empty() {
+            continue;
+        }
+        for m in s.members.iter() {
+            s.shared_fitness += fitnesses[*m];
+            total += fitnesses[*m];
+        }
+        if s.shared_fitness <= 0.0 {
+            continue;
+        }
+        s.shared_fitness /= s.members.len() as f32;
+    }
+    total /= environment.population_count as f32;
+    let total_fitness = species.iter().map(|s| s.shared_fitness).sum::<f32>();
+    for s in species.iter_mut() {
+        s.percent_total = s.shared_fitness / total_fitness;
+    }
+    let mut next_gen = vec![];
+    let mut remaining = environment.population_count as f32;
+    let mut next_gen_id = 0;
+    let best_idx = (0..population.len())
+        .max_by(|a, b| fitnesses[*a].partial_cmp(&fitnesses[*b]).unwrap())
+        .unwrap();
+    for s in species.iter() {
+        let mut offspring_count = (s.percent_total * environment.population_count as f32).floor();
+        remaining -= offspring_count;
+        if remaining <= 0.0 {
+            offspring_count += remaining;
+        }
+        if offspring_count >= 1.0 && s.members.contains(&best_idx) {
+            offspring_count -= 1.0;
+            let mut best = population[best_idx].clone();
+            best.id = next_gen_id;
+            next_gen_id += 1;
+            next_gen.push(best);
+        }
+        let only_mutate = (offspring_count * environment.only_mutate).floor();
+        let to_crossover = offspring_count - only_mutate;
+        let elites = elites(s, fitnesses, environment);
+        for _om in 0..only_mutate as usize {
+            let selected = elites[rng.gen_range(0..elites.len())];
+            let mut mutated =
+                environment.mutate(population[selected].clone(), existing_innovation, rng);
+            mutated.id = next_gen_id;
+            next_gen_id += 1;
+            next_gen.push(mutated);
+        }
+        for _c in 0..to_crossover as usize {
+            let parent1 = elites[rng.gen_range(0..elites.len())];
+            let mut parent2 = elites[rng.gen_range(0..elites.len())];
+            while population[parent1].id == population[parent2].id && elites.len() > 1 {
+                parent2 = elites[rng.gen_range(0..elites.len())];
+            }
+            let (best, other) = if fitnesses[parent1] > fitnesses[parent2] {
+                (parent1, parent2)
+            } else if fitnesses[parent2] > fitnesses[parent1] || rng.gen_range(0.0..1.0) < 0.5 {
+                (parent2, parent1)
+            } else {
+                (parent1, parent2)
+            };
+            let crossover = environment.crossover(
+                next_gen_id,
+                population[best].clone(),
+                population[other].clone(),
+                rng,
+            );
+            next_gen_id += 1;
+            let crossover = if rng.gen_range(0.0..1.0) < environment.crossover_only {
+                crossover
+            } else {
+                environment.mutate(crossover, existing_innovation, rng)
+            };
+            next_gen.push(crossover);
+        }
+    }
+    // rounding offspring counts down leaves slots over, the best genome's species fills them
+    let count = environment.population_count as usize;
+    next_gen.truncate(count);
+    if next_gen.len() < count {
+        let elites = species
+            .iter()
+            .find(|s| s.members.contains(&best_idx))
+            .map(|s| elites(s, fitnesses, environment))
+            .unwrap_or(vec![best_idx]);
+        while next_gen.len() < count {
+            let selected = elites[rng.gen_range(0..elites.len())];
+            let mut mutated =
+                environment.mutate(population[selected].clone(), existing_innovation, rng);
+            mutated.id = next_gen_id;
+            next_gen_id += 1;
+            next_gen.push(mutated);
+        }
+    }
+    (next_gen, total)
+}
+// the fittest elitism share of a species, at least one member
+fn elites(s: &Species, fitnesses: &[Fitness], environment: &Environment) -> Vec<usize> {
+    let mut members = s.members.clone();
+    members.sort_by(|a, b| fitnesses[*a].partial_cmp(&fitnesses[*b]).unwrap());
+    members.reverse();
+    let elite_bound = ((environment.elitism * members.len() as f32) as usize)
+        .min(members.len())
+        .max(1);
+    members[0..elite_bound].to_vec()
+}
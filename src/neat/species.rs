@@ -0,0 +1,73 @@
+use crate::neat::compatibility::Compatibility;
+use crate::neat::environment::Environment;
+use crate::neat::genome::Genome;
+use crate::neat::{Fitness, Generation, SpeciesId};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Species {
+    pub id: SpeciesId,
+    pub members: Vec<usize>,
+    pub last_improved: Generation,
+    pub representative: usize,
+    pub repr_genome: Genome,
+    pub max_fitness: Fitness,
+    pub shared_fitness: Fitness,
+    pub percent_total: f32,
+}
+impl Species {
+    pub fn new(
+        id: SpeciesId,
+        representative: usize,
+        repr_genome: Genome,
+        last_improved: Generation,
+    ) -> Self {
+        Self {
+            id,
+            members: vec![representative],
+            last_improved,
+            representative,
+            repr_genome,
+            max_fitness: 0.0,
+            shared_fitness: 0.0,
+            percent_total: 0.0,
+        }
+    }
+}
+pub fn speciate(
+    species: &mut Vec<Species>,
+    species_id_gen: &mut SpeciesId,
+    population: &mut [Genome],
+    generation: Generation,
+    environment: &Environment,
+    rng: &mut impl Rng,
+) {
+    for s in species.iter_mut() {
+        s.members.clear()
+    }
+    for (p, genome) in population.iter_mut().enumerate() {
+        let found = species.iter_mut().find(|s| {
+            Compatibility::between(genome, &s.repr_genome)
+                .distance(&environment.compatibility_factors)
+                < environment.compatibility_threshold
+        });
+        if let Some(s) = found {
+            // add to existing
+            s.members.push(p);
+            genome.species = s.id;
+        } else {
+            // new
+            let id = *species_id_gen;
+            *species_id_gen += 1;
+            genome.species = id;
+            species.push(Species::new(id, p, genome.clone(), generation));
+        }
+    }
+    species.retain(|s| !s.members.is_empty());
+    for s in species.iter_mut() {
+        let rand_idx = rng.gen_range(0..s.members.len());
+        s.representative = *s.members.get(rand_idx).unwrap();
+        s.repr_genome = population[s.representative].clone();
+    }
+}
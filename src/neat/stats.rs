@@ -0,0 +1,304 @@
+use crate::neat::population::Population;
+use crate::neat::task::{mean_metrics, Metrics, Outcome};
+use crate::neat::{Fitness, Generation, Innovation, SpeciesId};
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+// one row of the stats log, collected after evaluation and before evolving
+#[derive(Clone, Serialize)]
+pub struct GenerationStats {
+    pub generation: Generation,
+    pub best: Fitness,
+    pub mean: Fitness,
+    pub median: Fitness,
+    pub stddev: Fitness,
+    pub collected: f32,
+    pub steps: f32,
+    pub species: usize,
+    pub species_sizes: Vec<usize>,
+    pub nodes: f32,
+    pub connections: f32,
+    pub depth: f32,
+    pub innovations: Innovation,
+    pub seconds: f32,
+    pub elapsed: f32,
+    // task metrics averaged over the population, see Outcome::metrics
+    pub metrics: Metrics,
+    pub per_species: Vec<SpeciesStats>,
+}
+#[derive(Clone, Serialize)]
+pub struct SpeciesStats {
+    pub id: SpeciesId,
+    pub size: usize,
+    pub best: Fitness,
+    pub mean: Fitness,
+    pub metrics: Metrics,
+}
+impl SpeciesStats {
+    fn collect(population: &Population, outcomes: &[Outcome]) -> Vec<Self> {
+        population
+            .species
+            .iter()
+            .map(|s| {
+                let members = s
+                    .members
+                    .iter()
+                    .filter_map(|m| outcomes.get(*m))
+                    .collect::<Vec<_>>();
+                Self {
+                    id: s.id,
+                    size: s.members.len(),
+                    best: members
+                        .iter()
+                        .map(|o| o.fitness)
+                        .fold(Fitness::MIN, Fitness::max),
+                    mean: members.iter().map(|o| o.fitness).sum::<Fitness>()
+                        / members.len().max(1) as f32,
+                    metrics: mean_metrics(members),
+                }
+            })
+            .collect()
+    }
+    fn csv(&self, generation: Generation) -> String {
+        let mut columns = vec![
+            generation.to_string(),
+            self.id.to_string(),
+            self.size.to_string(),
+            self.best.to_string(),
+            self.mean.to_string(),
+        ];
+        columns.extend(self.metrics.values().map(|v| v.to_string()));
+        columns.join(",")
+    }
+}
+impl GenerationStats {
+    // outcomes are indexed like genomes, duration is how long this generation took
+    pub fn collect(
+        population: &Population,
+        outcomes: &[Outcome],
+        duration: Duration,
+        elapsed: Duration,
+    ) -> Self {
+        let count = outcomes.len().max(1) as f32;
+        let mut fitnesses = outcomes.iter().map(|o| o.fitness).collect::<Vec<_>>();
+        fitnesses.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mean = fitnesses.iter().sum::<Fitness>() / count;
+        let variance = fitnesses
+            .iter()
+            .map(|f| (f - mean).powi(2))
+            .sum::<Fitness>()
+            / count;
+        let median = match fitnesses.len() {
+            0 => 0.0,
+            n if n % 2 == 0 => (fitnesses[n / 2 - 1] + fitnesses[n / 2]) / 2.0,
+            n => fitnesses[n / 2],
+        };
+        let genomes = population.genomes.len().max(1) as f32;
+        Self {
+            generation: population.generation,
+            best: fitnesses.last().copied().unwrap_or_default(),
+            mean,
+            median,
+            stddev: variance.sqrt(),
+            collected: outcomes.iter().map(|o| o.collected).sum::<f32>() / count,
+            steps: outcomes.iter().map(|o| o.steps).sum::<f32>() / count,
+            species: population.species.len(),
+            species_sizes: population.species.iter().map(|s| s.members.len()).collect(),
+            nodes: population
+                .genomes
+                .iter()
+                .map(|g| g.nodes.len() as f32)
+                .sum::<f32>()
+                / genomes,
+            connections: population
+                .genomes
+                .iter()
+                .map(|g| g.connections.len() as f32)
+                .sum::<f32>()
+                / genomes,
+            depth: population
+                .genomes
+                .iter()
+                .map(|g| g.depth as f32)
+                .sum::<f32>()
+                / genomes,
+            innovations: population.existing_innovation.generator,
+            seconds: duration.as_secs_f32(),
+            elapsed: elapsed.as_secs_f32(),
+            metrics: mean_metrics(outcomes),
+            per_species: SpeciesStats::collect(population, outcomes),
+        }
+    }
+    const HEADER: &'static str = "generation,best,mean,median,stddev,collected,steps,species,species_sizes,nodes,connections,depth,innovations,seconds,elapsed";
+    const SPECIES_HEADER: &'static str = "generation,species,size,best,mean";
+    // metric columns follow the fixed ones in key order
+    fn header<'a>(fixed: &str, metrics: impl IntoIterator<Item = &'a str>) -> String {
+        let mut keys = metrics.into_iter().collect::<Vec<_>>();
+        keys.sort();
+        let mut header = fixed.to_string();
+        for key in keys {
+            header.push(',');
+            header.push_str(key);
+        }
+        header
+    }
+    fn csv(&self) -> String {
+        let sizes = self
+            .species_sizes
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(";");
+        let mut row = format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.generation,
+            self.best,
+            self.mean,
+            self.median,
+            self.stddev,
+            self.collected,
+            self.steps,
+            self.species,
+            sizes,
+            self.nodes,
+            self.connections,
+            self.depth,
+            self.innovations,
+            self.seconds,
+            self.elapsed
+        );
+        for value in self.metrics.values() {
+            row.push_str(&format!(",{}", value));
+        }
+        row
+    }
+}
+#[derive(Copy, Clone, PartialEq)]
+pub enum StatsFormat {
+    Csv,
+    Jsonl,
+}
+// appends one line per generation so resumed runs keep extending the same file,
+// csv puts one line per species into a sibling stats.species.csv
+pub struct StatsLog {
+    file: File,
+    species: Option<File>,
+    format: StatsFormat,
+}
+impl StatsLog {
+    // .jsonl / .json paths get json lines, anything else csv; metrics are the task's
+    // Task::metrics, a csv with rows for other columns fails with ErrorKind::InvalidData
+    pub fn open(path: impl AsRef<Path>, metrics: &[&str]) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let format = match path.extension().and_then(|e| e.to_str()) {
+            Some("jsonl") | Some("json") => StatsFormat::Jsonl,
+            _ => StatsFormat::Csv,
+        };
+        let append = |path: &Path| OpenOptions::new().create(true).append(true).open(path);
+        let species = match format {
+            StatsFormat::Csv => {
+                let header =
+                    GenerationStats::header(GenerationStats::HEADER, metrics.iter().copied());
+                Self::check_header(path, &header)?;
+                let species_path = Self::species_path(path);
+                let species_header = GenerationStats::header(
+                    GenerationStats::SPECIES_HEADER,
+                    metrics.iter().copied(),
+                );
+                Self::check_header(&species_path, &species_header)?;
+                Some(append(&species_path)?)
+            }
+            StatsFormat::Jsonl => None,
+        };
+        Ok(Self {
+            file: append(path)?,
+            species,
+            format,
+        })
+    }
+    fn species_path(path: &Path) -> PathBuf {
+        let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("stats");
+        path.with_file_name(format!("{}.species.csv", stem))
+    }
+    // a csv that already has rows must have the columns of this run, anything
+    // else (another task, other metrics) fails with ErrorKind::InvalidData
+    fn check_header(path: &Path, header: &str) -> std::io::Result<()> {
+        let existing = match File::open(path) {
+            Ok(file) => BufReader::new(file).lines().next().transpose()?,
+            Err(e) if e.kind() == ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+        match existing {
+            Some(existing) if existing != header => Err(std::io::Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{} has the columns {} but this run writes {}, pick another stats path",
+                    path.display(),
+                    existing,
+                    header
+                ),
+            )),
+            _ => Ok(()),
+        }
+    }
+    pub fn write(&mut self, stats: &GenerationStats) -> std::io::Result<()> {
+        match self.format {
+            StatsFormat::Csv => {
+                let header =
+                    GenerationStats::header(GenerationStats::HEADER, stats.metrics.keys().copied());
+                let metrics = stats
+                    .per_species
+                    .first()
+                    .map(|s| s.metrics.clone())
+                    .unwrap_or_default();
+                let species_header = GenerationStats::header(
+                    GenerationStats::SPECIES_HEADER,
+                    metrics.keys().copied(),
+                );
+                if self.file.metadata()?.len() == 0 {
+                    writeln!(self.file, "{}", header)?;
+                }
+                writeln!(self.file, "{}", stats.csv())?;
+                if let Some(species) = self.species.as_mut() {
+                    if species.metadata()?.len() == 0 {
+                        writeln!(species, "{}", species_header)?;
+                    }
+                    for s in stats.per_species.iter() {
+                        writeln!(species, "{}", s.csv(stats.generation))?;
+                    }
+                }
+                Ok(())
+            }
+            StatsFormat::Jsonl => writeln!(self.file, "{}", serde_json::to_string(stats)?),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_header_rejects_other_columns() {
+        let path = std::env::temp_dir().join(format!("snake-neat-{}.csv", std::process::id()));
+        std::fs::write(&path, "generation,best\n0,1\n").unwrap();
+        assert!(StatsLog::check_header(&path, "generation,best").is_ok());
+        let error = StatsLog::check_header(&path, "generation,best,length").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+    }
+    #[test]
+    fn open_refuses_a_log_of_other_metrics() {
+        let path = std::env::temp_dir().join(format!("snake-neat-open-{}.csv", std::process::id()));
+        let species = StatsLog::species_path(&path);
+        let header = GenerationStats::header(GenerationStats::HEADER, ["length"]);
+        std::fs::write(&path, format!("{}\n", header)).unwrap();
+        assert!(StatsLog::open(&path, &["length"]).is_ok());
+        let error = StatsLog::open(&path, &[]).err().unwrap();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&species).unwrap();
+    }
+}
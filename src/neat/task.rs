@@ -0,0 +1,175 @@
+use crate::neat::environment::Environment;
+use crate::neat::network::Network;
+use crate::neat::population::Population;
+use crate::neat::{Fitness, GameRng};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// named task specific measurements, averaged wherever outcomes are combined
+pub type Metrics = BTreeMap<&'static str, f32>;
+// what one evaluation produced, collected is task specific (food for snake)
+#[derive(Clone, Default, Debug)]
+pub struct Outcome {
+    pub fitness: Fitness,
+    pub collected: f32,
+    pub steps: f32,
+    pub metrics: Metrics,
+}
+// a key missing from some outcomes counts as 0 there
+pub fn mean_metrics<'a>(outcomes: impl IntoIterator<Item = &'a Outcome>) -> Metrics {
+    let mut metrics = Metrics::new();
+    let mut count = 0;
+    for outcome in outcomes {
+        count += 1;
+        for (key, value) in outcome.metrics.iter() {
+            *metrics.entry(key).or_default() += value;
+        }
+    }
+    for value in metrics.values_mut() {
+        *value /= count.max(1) as f32;
+    }
+    metrics
+}
+// how the trials of one genome fold into the fitness used for selection
+#[derive(Copy, Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Aggregation {
+    #[default]
+    Mean,
+    Median,
+    Min,
+    // mean - environment.stddev_penalty * stddev, punishes lucky one-offs
+    MeanMinusStddev,
+}
+impl Aggregation {
+    // collected + steps are always averaged, only fitness follows the mode
+    pub fn aggregate(&self, trials: &[Outcome], stddev_penalty: f32) -> Outcome {
+        if trials.is_empty() {
+            return Outcome::default();
+        }
+        let count = trials.len() as f32;
+        let mut fitnesses = trials.iter().map(|t| t.fitness).collect::<Vec<_>>();
+        fitnesses.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mean = fitnesses.iter().sum::<Fitness>() / count;
+        let fitness = match self {
+            Aggregation::Mean => mean,
+            Aggregation::Median => {
+                let n = fitnesses.len();
+                if n % 2 == 0 {
+                    (fitnesses[n / 2 - 1] + fitnesses[n / 2]) / 2.0
+                } else {
+                    fitnesses[n / 2]
+                }
+            }
+            Aggregation::Min => fitnesses[0],
+            Aggregation::MeanMinusStddev => {
+                let variance = fitnesses
+                    .iter()
+                    .map(|f| (f - mean).powi(2))
+                    .sum::<Fitness>()
+                    / count;
+                mean - stddev_penalty * variance.sqrt()
+            }
+        };
+        Outcome {
+            fitness,
+            collected: trials.iter().map(|t| t.collected).sum::<f32>() / count,
+            steps: trials.iter().map(|t| t.steps).sum::<f32>() / count,
+            metrics: mean_metrics(trials),
+        }
+    }
+}
+pub trait Task: Sync {
+    fn name(&self) -> &str;
+    fn input_size(&self) -> usize;
+    fn output_size(&self) -> usize;
+    // network is compiled once per genome and shared by all of its trials
+    fn evaluate(&self, network: &Network, environment: &Environment, rng: &mut GameRng) -> Outcome;
+    fn solved(&self, _fitness: Fitness) -> bool {
+        false
+    }
+    // the keys of Outcome::metrics, known up front so a stats log can check its columns on open
+    fn metrics(&self) -> Vec<&'static str> {
+        vec![]
+    }
+    fn environment(&self) -> Environment {
+        // parameters from the original NEAT paper
+        let mut environment = Environment::new();
+        environment.population_count = 150;
+        environment.input_size = self.input_size();
+        environment.output_size = self.output_size();
+        environment.compatibility_factors.c1 = 1.0;
+        environment.compatibility_factors.c2 = 1.0;
+        environment.compatibility_factors.c3 = 0.4;
+        environment.compatibility_threshold = 3.0;
+        environment.stagnation_threshold = 15;
+        environment.elitism = 0.2;
+        environment.add_connection = 0.05;
+        environment.add_node = 0.03;
+        environment.inherit_disable = 0.75;
+        environment.only_mutate = 0.25;
+        environment.crossover_only = 0.2;
+        environment.connection_weight = 0.8;
+        environment.perturb = 0.9;
+        environment
+    }
+}
+// plays environment.trials games of genome index, each on its own game stream
+pub fn trials(task: &(impl Task + ?Sized), population: &Population, index: usize) -> Vec<Outcome> {
+    let network = Network::compile(&population.genomes[index], &population.environment);
+    (0..population.environment.trials.max(1))
+        .map(|trial| {
+            let mut rng = population.game_rng(index, trial);
+            task.evaluate(&network, &population.environment, &mut rng)
+        })
+        .collect()
+}
+// the aggregated outcome selection sees
+pub fn evaluate(task: &(impl Task + ?Sized), population: &Population, index: usize) -> Outcome {
+    let environment = &population.environment;
+    environment
+        .aggregation
+        .aggregate(&trials(task, population, index), environment.stddev_penalty)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trials(fitnesses: &[Fitness]) -> Vec<Outcome> {
+        fitnesses
+            .iter()
+            .enumerate()
+            .map(|(i, f)| Outcome {
+                fitness: *f,
+                collected: i as f32,
+                steps: 10.0,
+                metrics: Metrics::from([("length", i as f32)]),
+            })
+            .collect()
+    }
+    #[test]
+    fn aggregate_folds_fitness_by_mode() {
+        let trials = trials(&[4.0, 1.0, 7.0]);
+        assert_eq!(Aggregation::Mean.aggregate(&trials, 1.0).fitness, 4.0);
+        assert_eq!(Aggregation::Median.aggregate(&trials, 1.0).fitness, 4.0);
+        assert_eq!(Aggregation::Min.aggregate(&trials, 1.0).fitness, 1.0);
+        let stddev = 6.0f32.sqrt();
+        let fitness = Aggregation::MeanMinusStddev.aggregate(&trials, 2.0).fitness;
+        assert!((fitness - (4.0 - 2.0 * stddev)).abs() < 1e-5);
+    }
+    #[test]
+    fn median_of_an_even_count_is_the_middle_mean() {
+        let trials = trials(&[1.0, 2.0, 4.0, 8.0]);
+        assert_eq!(Aggregation::Median.aggregate(&trials, 1.0).fitness, 3.0);
+    }
+    #[test]
+    fn aggregate_averages_everything_else() {
+        let outcome = Aggregation::Min.aggregate(&trials(&[4.0, 1.0, 7.0]), 1.0);
+        assert_eq!(outcome.collected, 1.0);
+        assert_eq!(outcome.steps, 10.0);
+        assert_eq!(outcome.metrics["length"], 1.0);
+    }
+    #[test]
+    fn no_trials_aggregate_to_nothing() {
+        assert_eq!(Aggregation::Mean.aggregate(&[], 1.0).fitness, 0.0);
+    }
+}
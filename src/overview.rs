@@ -1,7 +1,6 @@
 use crate::intro::{IntroIn, IntroOut};
 use crate::runner::game::{ComputeReward, GameSpeed, MoveWithNetworkOutput, SetNetworkInput};
-use crate::runner::genome::{Activate, MaxDepthCheck};
-use crate::runner::species::Speciate;
+use crate::runner::genome::Activate;
 use crate::runner::{
     AddGame, Evaluate, EvaluateGenome, GameSpeedChange, Process, RunnerIn, RunnerOut,
     UpdateGenerationText, UpdateSpeciesCountText,
@@ -251,9 +250,7 @@ impl Branch for Overview {
         tree.observe(SetNetworkInput::obs);
         tree.observe(MoveWithNetworkOutput::obs);
         tree.observe(ComputeReward::obs);
-        tree.observe(MaxDepthCheck::obs);
         tree.observe(Activate::obs);
-        tree.observe(Speciate::obs);
         tree.observe(AddGame::obs);
         tree.insert_resource(id_table);
         tree.insert_resource(CurrentSection { id: 1 });
@@ -1,10 +1,6 @@
 use crate::overview::{IconHandles, SECTION_OUT_END, SIDE_PANEL_WIDTH, VIEW_AREA};
-use crate::runner::game::{Game, GameGrid, Running};
-use crate::runner::genome::{
-    Activations, Evaluation, MaxDepthCheck, NetworkInput, NetworkOutput, Reward,
-};
-use crate::runner::species::{Speciate, Species};
-use environment::Environment;
+use crate::runner::game::{Game, Running};
+use crate::runner::genome::NetworkState;
 use foliage::anim::Animation;
 use foliage::bevy_ecs::component::Component;
 use foliage::bevy_ecs::entity::Entity;
@@ -27,18 +23,23 @@ use foliage::tree::{EcsExtension, Tree};
 use foliage::twig::button::Button;
 use foliage::{bevy_ecs, Root};
 use game::GameSpeed;
-use genome::Genome;
-use innovation::ExistingInnovation;
-use rand::Rng;
+use snake_neat::config::Config;
+use snake_neat::neat::checkpoint::Checkpoint;
+use snake_neat::neat::genome::Genome;
+use snake_neat::neat::genome_file::GenomeFile;
+use snake_neat::neat::network::Network;
+use snake_neat::neat::population::Population;
+use snake_neat::neat::stats::{GenerationStats, StatsLog};
+use snake_neat::neat::task::{Outcome, Task};
+use snake_neat::neat::Generation;
+use snake_neat::snake::fitness::FitnessFunction;
+use snake_neat::snake::sim::SnakeSim;
+use snake_neat::snake::{Evaluation, NetworkInput, NetworkOutput, SnakeTask};
+use std::io::ErrorKind;
+use std::time::Instant;
 
-mod compatibility;
-mod connection;
-pub(crate) mod environment;
 pub(crate) mod game;
 pub(crate) mod genome;
-mod innovation;
-mod node;
-pub(crate) mod species;
 impl Root for Runner {
     fn attach(elm: &mut Elm) {
         elm.scheduler
@@ -76,24 +77,40 @@ pub(crate) struct RunnerIds {
 }
 impl RunnerIn {
     pub(crate) fn obs(trigger: Trigger<Self>, mut tree: Tree) {
-        let mut environment = Environment::new();
-        environment.population_count = 150;
-        environment.input_size = 6;
-        environment.output_size = 2;
-        environment.compatibility_factors.c1 = 1.0;
-        environment.compatibility_factors.c2 = 1.0;
-        environment.compatibility_factors.c3 = 0.4;
-        environment.compatibility_threshold = 3.0;
-        environment.stagnation_threshold = 15;
-        environment.elitism = 0.2;
-        environment.add_connection = 0.2;
-        environment.add_node = 0.07;
-        environment.inherit_disable = 0.75;
-        environment.only_mutate = 0.25;
-        environment.crossover_only = 0.2;
-        environment.connection_weight = 0.8;
-        environment.perturb = 0.9;
-        environment.max_turns = 5000;
+        let options = RunOptions::from_args();
+        let config = match options.config.as_ref() {
+            Some(path) => Config::load(path).unwrap_or_else(|e| panic!("{}", e)),
+            None => Config::default(),
+        };
+        let task = config.snake_task();
+        let (population, best, bests, averages) = match options.resume.as_ref() {
+            Some(path) => {
+                let checkpoint = Checkpoint::load(path).unwrap_or_else(|e| panic!("{}", e));
+                let environment = &checkpoint.population.environment;
+                if environment.input_size != task.input_size()
+                    || environment.output_size != task.output_size()
+                {
+                    panic!(
+                        "checkpoint {} was written with a different snake.observation or snake.actions",
+                        path
+                    );
+                }
+                (
+                    checkpoint.population,
+                    checkpoint.best,
+                    checkpoint.bests,
+                    checkpoint.averages,
+                )
+            }
+            None => {
+                let mut environment = task.environment();
+                config.apply(&mut environment);
+                let seed = options.seed.or(config.seed).unwrap_or_else(rand::random);
+                println!("seed: {}", seed);
+                (Population::seeded(environment, seed), None, vec![], vec![])
+            }
+        };
+        let environment = &population.environment;
         tree.start_sequence(|seq| {
             seq.animate(
                 Animation::new(Opacity::new(1.0))
@@ -265,8 +282,8 @@ impl RunnerIn {
                     IconHandles::Table,
                     Coloring::new(Grey::minus_two(), Grey::plus_two()),
                 )
-                    .with_text("Results", FontSize::new(14))
-                    .rounded(Rounding::all(0.2)),
+                .with_text("Results", FontSize::new(14))
+                .rounded(Rounding::all(0.2)),
             )
             .insert(
                 ResponsiveLocation::new()
@@ -360,11 +377,17 @@ impl RunnerIn {
             .insert(EvaluateCore::recursive())
             .id();
         tree.insert_resource(GameSpeed::new(1));
-        let game_grid = GameGrid::new(30, 30);
         let best_evaluator = tree.spawn(Leaf::new().stem(Some(root)).elevation(-1)).id(); // genome
         let best_label = tree
             .spawn(Leaf::new().stem(Some(root)).elevation(-1))
-            .insert(Text::new("Best: 0", FontSize::new(16), Grey::plus_two()).centered())
+            .insert(
+                Text::new(
+                    format!("Best: {}", best.as_ref().map(|b| b.1).unwrap_or_default()),
+                    FontSize::new(16),
+                    Grey::plus_two(),
+                )
+                .centered(),
+            )
             .insert(
                 ResponsiveLocation::new()
                     .left(stem().left())
@@ -412,28 +435,52 @@ impl RunnerIn {
             .observe(BestPrintGenome::obs)
             .insert(EvaluateCore::recursive())
             .id();
+        let population_count = environment.population_count;
+        let best = best
+            .map(|(genome, fitness)| {
+                let evaluation = Evaluation {
+                    fitness,
+                    ..Evaluation::default()
+                };
+                (genome, evaluation)
+            })
+            .unwrap_or_else(|| (population.genomes[0].clone(), Evaluation::default()));
+        let requested_generation = population.generation + 1;
+        let stats = match StatsLog::open(&options.stats, &task.metrics()) {
+            Ok(stats) => Some(stats),
+            // a log of another run stays untouched
+            Err(e) if e.kind() == ErrorKind::InvalidData => panic!("{}", e),
+            Err(e) => {
+                println!("failed to open stats log {}: {}", options.stats, e);
+                None
+            }
+        };
         let mut runner = Runner {
-            population: vec![],
-            species: vec![],
-            generation: 0,
-            requested_generation: 1,
+            slots: vec![],
+            networks: vec![],
+            population,
+            requested_generation,
             run_to: false,
-            best: None,
+            best: Some(best),
             best_evaluator,
-            species_id_gen: 0,
-            genome_id_gen: 0,
-            finished: environment.population_count,
-            game_grid,
+            finished: population_count,
+            fitness: task.fitness_function(),
+            task,
             canvas_size: (0, 0),
             total: 0.0,
-            averages: vec![],
-            bests: vec![],
+            averages,
+            bests,
+            options,
+            stats,
+            started: Instant::now(),
+            generation_started: Instant::now(),
         };
+        runner.compile();
         let main = VIEW_AREA.0 as i32 - SIDE_PANEL_WIDTH as i32 - side;
         let element_label = 24;
         let element_size = (100, 100 + element_label);
         let num_columns = main / (element_size.0 + 8);
-        let num_rows = (environment.population_count as f32 / num_columns as f32)
+        let num_rows = (population_count as f32 / num_columns as f32)
             .ceil()
             .max(1.0) as i32;
         let view_size = (
@@ -467,7 +514,6 @@ impl RunnerIn {
             .insert(ScrollContext::new(grid_wrapper))
             .insert(EvaluateCore::recursive())
             .id();
-        let reward = Reward::new(5.0, 1.75, 0.75);
         let mut locations = vec![];
         for r in 0..num_rows {
             for c in 0..num_columns {
@@ -482,7 +528,7 @@ impl RunnerIn {
                 );
             }
         }
-        for p in 0..environment.population_count {
+        for p in 0..population_count {
             let view = tree
                 .spawn(Leaf::new().stem(Some(grid)).elevation(-1))
                 .insert(locations.get(p as usize).unwrap().clone())
@@ -529,38 +575,28 @@ impl RunnerIn {
                 score: score_label,
                 finished_signal,
                 genome: g,
+                index: p as usize,
             });
-            let genome = Genome::new(
-                runner.genome_id_gen,
-                environment.input_size,
-                environment.output_size,
+            let game = Game::new(
+                &mut tree,
+                grid_wrapper,
+                g,
+                runner.sim(p as usize, 0),
+                canvas_size,
             );
-            tree.entity(g).insert(genome);
-            let game = Game::new(&mut tree, grid_wrapper, g, game_grid, canvas_size);
             tree.entity(g)
                 .insert(game)
                 .insert(Running(false))
                 .insert(Evaluation::default())
                 .insert(NetworkInput::default())
                 .insert(NetworkOutput::default())
-                .insert(reward)
-                .insert(Activations::default())
+                .insert(NetworkState::default())
                 .insert(EvaluateCore::recursive());
-            runner.population.push(g);
-            runner.genome_id_gen += 1;
+            runner.slots.push(g);
         }
         let expanded_view = tree.spawn(Leaf::new().stem(Some(root)).elevation(-1)).id();
         // TODO elements of expanded-view (game, network, score-label, finished-signal, switch-view)
-        runner.best.replace((
-            Genome::new(0, environment.input_size, environment.output_size),
-            Evaluation::default(),
-        ));
-        tree.insert_resource(ExistingInnovation::new(
-            environment.input_size,
-            environment.output_size,
-        ));
         tree.insert_resource(runner);
-        tree.insert_resource(environment);
         let ids = RunnerIds {
             root,
             gen,
@@ -585,7 +621,8 @@ impl RunnerIn {
             best_evaluate,
         };
         tree.insert_resource(ids);
-        tree.trigger(Speciate {});
+        tree.trigger(UpdateGenerationText {});
+        tree.trigger(UpdateSpeciesCountText {});
     }
 }
 #[derive(Event)]
@@ -601,30 +638,100 @@ impl RunnerOut {
         tree.entity(ids.root).despawn();
     }
 }
-pub(crate) type NodeId = usize;
-pub(crate) type GenomeId = usize;
-pub(crate) type SpeciesId = usize;
-pub(crate) type Generation = i32;
-pub(crate) type Innovation = i32;
-pub(crate) type Depth = i32;
-pub(crate) type Fitness = f32;
 #[derive(Resource)]
 pub(crate) struct Runner {
-    pub(crate) population: Vec<Entity>,
-    pub(crate) species: Vec<Species>,
-    pub(crate) generation: Generation,
+    pub(crate) slots: Vec<Entity>,
+    pub(crate) population: Population,
+    pub(crate) networks: Vec<Network>,
     pub(crate) requested_generation: Generation,
     pub(crate) run_to: bool,
     pub(crate) best: Option<(Genome, Evaluation)>,
     pub(crate) best_evaluator: Entity,
-    pub(crate) species_id_gen: SpeciesId,
-    pub(crate) genome_id_gen: GenomeId,
     pub(crate) finished: i32,
-    pub(crate) game_grid: GameGrid,
+    pub(crate) task: SnakeTask,
+    pub(crate) fitness: Box<dyn FitnessFunction>,
     pub(crate) canvas_size: (i32, i32),
     pub(crate) total: f32,
     pub(crate) averages: Vec<f32>,
     pub(crate) bests: Vec<f32>,
+    pub(crate) options: RunOptions,
+    pub(crate) stats: Option<StatsLog>,
+    pub(crate) started: Instant,
+    pub(crate) generation_started: Instant,
+}
+pub(crate) struct RunOptions {
+    pub(crate) resume: Option<String>,
+    pub(crate) checkpoint: Option<String>,
+    pub(crate) checkpoint_every: Generation,
+    pub(crate) seed: Option<u64>,
+    pub(crate) config: Option<String>,
+    pub(crate) stats: String,
+}
+impl RunOptions {
+    pub(crate) fn from_args() -> Self {
+        let mut options = Self {
+            resume: None,
+            checkpoint: None,
+            checkpoint_every: 10,
+            seed: None,
+            config: None,
+            stats: "stats.csv".to_string(),
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--resume" => options.resume = args.next(),
+                "--checkpoint" => options.checkpoint = args.next(),
+                "--config" => options.config = args.next(),
+                "--stats" => options.stats = args.next().expect("--stats expects a path"),
+                "--seed" => {
+                    options.seed = Some(
+                        args.next()
+                            .and_then(|n| n.parse().ok())
+                            .expect("--seed must be a number"),
+                    )
+                }
+                "--every" => {
+                    options.checkpoint_every = args
+                        .next()
+                        .and_then(|n| n.parse().ok())
+                        .expect("--every must be a number")
+                }
+                _ => panic!("unexpected argument {}", arg),
+            }
+        }
+        options
+    }
+}
+impl Runner {
+    pub(crate) fn sim(&self, index: usize, trial: i32) -> SnakeSim {
+        self.task.sim(
+            &self.population.environment,
+            self.population.game_rng(index, trial),
+        )
+    }
+    pub(crate) fn compile(&mut self) {
+        self.networks = self
+            .population
+            .genomes
+            .iter()
+            .map(|g| Network::compile(g, &self.population.environment))
+            .collect();
+    }
+    pub(crate) fn save_checkpoint(&self) {
+        let Some(path) = self.options.checkpoint.as_ref() else {
+            return;
+        };
+        let checkpoint = Checkpoint::new(
+            self.population.clone(),
+            self.best.as_ref().map(|(g, e)| (g.clone(), e.fitness)),
+            self.bests.clone(),
+            self.averages.clone(),
+        );
+        if let Err(e) = checkpoint.save(path) {
+            println!("failed to write checkpoint {}: {}", path, e);
+        }
+    }
 }
 #[derive(Event)]
 pub(crate) struct GameSpeedChange(pub(crate) i32);
@@ -651,6 +758,7 @@ pub(crate) struct GenomeView {
     pub(crate) score: Entity,
     pub(crate) finished_signal: Entity,
     pub(crate) genome: Entity,
+    pub(crate) index: usize,
 }
 #[derive(Event)]
 pub(crate) struct UpdateSpeciesCountText {}
@@ -661,14 +769,15 @@ impl UpdateSpeciesCountText {
         mut text: Query<&mut TextValue>,
         runner: Res<Runner>,
     ) {
-        text.get_mut(ids.species_label).unwrap().0 = format!("Species: {}", runner.species.len());
+        text.get_mut(ids.species_label).unwrap().0 =
+            format!("Species: {}", runner.population.species.len());
     }
 }
 pub(crate) struct StopGeneration {}
 impl StopGeneration {
     pub(crate) fn obs(trigger: Trigger<OnClick>, mut tree: Tree, mut runner: ResMut<Runner>) {
         runner.run_to = false;
-        for genome in runner.population.iter() {
+        for genome in runner.slots.iter() {
             tree.entity(*genome).insert(Running(false));
         }
     }
@@ -684,7 +793,7 @@ impl UpdateGenerationText {
     ) {
         text.get_mut(ids.gen_text).unwrap().0 = format!(
             "Gen: {} -> {}",
-            runner.generation, runner.requested_generation
+            runner.population.generation, runner.requested_generation
         );
     }
 }
@@ -727,9 +836,10 @@ impl EvaluateWrapper {
 #[derive(Event)]
 pub(crate) struct Evaluate {}
 impl Evaluate {
-    pub(crate) fn obs(trigger: Trigger<Self>, mut tree: Tree, runner: ResMut<Runner>) {
+    pub(crate) fn obs(trigger: Trigger<Self>, mut tree: Tree, mut runner: ResMut<Runner>) {
+        runner.generation_started = Instant::now();
         // run game instance to completion on each genome
-        for genome in runner.population.iter().cloned() {
+        for genome in runner.slots.iter().cloned() {
             tree.trigger_targets(EvaluateGenome {}, genome);
         }
     }
@@ -747,6 +857,8 @@ impl EvaluateGenome {
         let genome = trigger.entity();
         let view = views.get(genome).unwrap();
         tree.entity(genome).insert(Evaluation::default());
+        tree.entity(genome)
+            .insert(NetworkState(runner.networks[view.index].activations()));
         tree.entity(genome).remove::<Game>();
         tree.trigger_targets(AddGame {}, genome);
         tree.entity(genome).insert(Running(true));
@@ -755,7 +867,7 @@ impl EvaluateGenome {
         runner.finished = (runner.finished - 1).max(0);
         tree.entity(ids.num_running).insert(TextValue::new(format!(
             "Running: {}",
-            runner.population.len() - runner.finished as usize
+            runner.slots.len() - runner.finished as usize
         )));
     }
 }
@@ -767,13 +879,15 @@ impl AddGame {
         mut tree: Tree,
         runner: Res<Runner>,
         ids: Res<RunnerIds>,
+        views: Query<&GenomeView>,
     ) {
         let genome = trigger.entity();
+        let index = views.get(genome).unwrap().index;
         let game = Game::new(
             &mut tree,
             ids.grid_wrapper,
             genome,
-            runner.game_grid,
+            runner.sim(index, 0),
             runner.canvas_size,
         );
         tree.entity(genome).insert(game);
@@ -784,6 +898,7 @@ impl PrintResults {
     pub(crate) fn obs(trigger: Trigger<OnClick>, mut tree: Tree, runner: Res<Runner>) {
         std::fs::write("bests.txt", format!("{:?}", runner.bests)).unwrap();
         std::fs::write("averages.txt", format!("{:?}", runner.averages)).unwrap();
+        runner.save_checkpoint();
     }
 }
 #[derive(Event)]
@@ -800,199 +915,44 @@ impl Process {
         trigger: Trigger<Self>,
         mut tree: Tree,
         mut runner: ResMut<Runner>,
-        mut existing_innovation: ResMut<ExistingInnovation>,
-        evaluations: Query<(Entity, &Evaluation)>,
-        environment: Res<Environment>,
-        genomes: Query<&Genome>,
+        evaluations: Query<&Evaluation>,
+        views: Query<&GenomeView>,
     ) {
-        // species %
-        let mut to_cull = vec![];
-        let gen = runner.generation;
-        for species in runner.species.iter_mut() {
-            let max = species
-                .members
-                .iter()
-                .map(|e| evaluations.get(*e).unwrap().1.fitness)
-                .max_by(|a, b| a.partial_cmp(b).unwrap())
-                .unwrap_or_default();
-            if max > species.max_fitness {
-                species.max_fitness = max;
-                species.last_improved = gen;
-            }
-
-            if gen > species.last_improved + environment.stagnation_threshold {
-                to_cull.push(species.id);
-            }
-        }
-        // cull
-        for id in to_cull.iter_mut() {
-            *id = runner.species.iter().position(|s| s.id == *id).unwrap();
-        }
-        to_cull.sort();
-        to_cull.reverse();
-        for idx in to_cull {
-            if runner.species.len() == 1 {
-                // replace all with starting genomes
-                // put all into starting species
-                // for now continuing
-                continue;
-            }
-            runner.species.remove(idx);
+        let mut outcomes = vec![Outcome::default(); runner.population.genomes.len()];
+        for slot in runner.slots.iter() {
+            outcomes[views.get(*slot).unwrap().index] = evaluations
+                .get(*slot)
+                .unwrap()
+                .aggregate(&runner.population.environment);
         }
-        let mut total = 0.0;
-        for species in runner.species.iter_mut() {
-            species.shared_fitness = 0.0;
-            if species.members.is_empty() {
-                continue;
-            }
-            for e in species.members.iter() {
-                let eval = *evaluations.get(*e).unwrap().1;
-                species.shared_fitness += eval.fitness;
-                total += eval.fitness;
-            }
-            if species.shared_fitness <= 0.0 {
-                continue;
+        let fitnesses = outcomes.iter().map(|o| o.fitness).collect::<Vec<_>>();
+        let generation_stats = GenerationStats::collect(
+            &runner.population,
+            &outcomes,
+            runner.generation_started.elapsed(),
+            runner.started.elapsed(),
+        );
+        let path = runner.options.stats.clone();
+        if let Some(stats) = runner.stats.as_mut() {
+            if let Err(e) = stats.write(&generation_stats) {
+                println!("failed to write stats log {}: {}", path, e);
             }
-            species.shared_fitness /= species.members.len() as f32;
         }
-        total /= environment.population_count as f32;
+        let total = runner.population.evolve(&fitnesses);
+        runner.compile();
         runner.total = total;
         runner.averages.push(total);
-        let total_fitness = runner.species.iter().map(|s| s.shared_fitness).sum::<f32>();
-        for species in runner.species.iter_mut() {
-            species.percent_total = species.shared_fitness / total_fitness;
-        }
-        let mut next_gen = vec![];
-        let mut remaining = environment.population_count as f32;
-        let mut next_gen_id = 0;
-        let best_id = runner
-            .population
-            .iter()
-            .max_by(|a, b| {
-                evaluations
-                    .get(**a)
-                    .unwrap()
-                    .1
-                    .fitness
-                    .partial_cmp(&evaluations.get(**b).unwrap().1.fitness)
-                    .unwrap()
-            })
-            .unwrap();
-        let best_id = genomes.get(*best_id).unwrap().id;
-        for species in runner.species.iter_mut() {
-            let mut offspring_count =
-                (species.percent_total * environment.population_count as f32).floor();
-            remaining -= offspring_count;
-            if remaining <= 0.0 {
-                offspring_count += remaining;
-            }
-            if offspring_count >= 1.0 {
-                if let Some(idx) = species
-                    .members
-                    .iter()
-                    .map(|m| genomes.get(*m).unwrap().id)
-                    .position(|i| i == best_id)
-                {
-                    offspring_count -= 1.0;
-                    let mut best = genomes
-                        .get(*species.members.get(idx).unwrap())
-                        .cloned()
-                        .unwrap();
-                    best.id = next_gen_id;
-                    next_gen_id += 1;
-                    next_gen.push(best);
-                }
-            }
-            let only_mutate = (offspring_count * environment.only_mutate).floor();
-            let to_crossover = offspring_count - only_mutate;
-            let mut members = species
-                .members
-                .iter()
-                .map(|m| evaluations.get(*m).unwrap())
-                .map(|e| (e.0, *e.1))
-                .collect::<Vec<_>>();
-            members.sort_by(|a, b| a.1.fitness.partial_cmp(&b.1.fitness).unwrap());
-            members.reverse();
-            let elite_bound = ((environment.elitism * members.len() as f32) as usize)
-                .min(members.len())
-                .max(1);
-            let elites = members.get(0..elite_bound).unwrap().to_vec();
-            for _om in 0..only_mutate as usize {
-                let selected = elites
-                    .get(rand::thread_rng().gen_range(0..elites.len()))
-                    .copied()
-                    .unwrap();
-                let mut mutated = environment.mutate(
-                    genomes.get(selected.0).unwrap().clone(),
-                    &mut existing_innovation,
-                );
-                mutated.id = next_gen_id;
-                next_gen_id += 1;
-                next_gen.push(mutated);
-            }
-            for _c in 0..to_crossover as usize {
-                let parent1 = elites
-                    .get(rand::thread_rng().gen_range(0..elites.len()))
-                    .cloned()
-                    .unwrap();
-                let parent1_genome = genomes.get(parent1.0).unwrap().clone();
-                let mut parent2 = elites
-                    .get(rand::thread_rng().gen_range(0..elites.len()))
-                    .cloned()
-                    .unwrap();
-                let mut parent2_genome = genomes.get(parent2.0).unwrap().clone();
-                while parent1_genome.id == parent2_genome.id && elites.len() > 1 {
-                    parent2 = elites
-                        .get(rand::thread_rng().gen_range(0..elites.len()))
-                        .cloned()
-                        .unwrap();
-                    parent2_genome = genomes.get(parent2.0).cloned().unwrap();
-                }
-                let (best, other) = if parent1.1.fitness > parent2.1.fitness {
-                    (parent1_genome, parent2_genome)
-                } else if parent2.1.fitness > parent1.1.fitness {
-                    (parent2_genome, parent1_genome)
-                } else {
-                    if rand::thread_rng().gen_range(0.0..1.0) < 0.5 {
-                        (parent2_genome, parent1_genome)
-                    } else {
-                        (parent1_genome, parent2_genome)
-                    }
-                };
-                let crossover = environment.crossover(next_gen_id, best, other);
-                next_gen_id += 1;
-                let crossover =
-                    if rand::thread_rng().gen_range(0.0..1.0) < environment.crossover_only {
-                        crossover
-                    } else {
-                        environment.mutate(crossover, &mut existing_innovation)
-                    };
-                next_gen.push(crossover);
-            }
-        }
-        if runner.population.len() != next_gen.len() {
-            println!(
-                "pop: {} next-gen: {}",
-                runner.population.len(),
-                next_gen.len()
-            );
+        if runner.options.checkpoint_every > 0
+            && runner.population.generation % runner.options.checkpoint_every == 0
+        {
+            runner.save_checkpoint();
         }
-        for (i, next) in next_gen.drain(..).enumerate() {
-            if let Some(entity) = runner.population.get(i) {
-                tree.entity(*entity).insert(next);
-            }
-        }
-        runner.generation += 1;
-        // max-depth
-        tree.trigger(MaxDepthCheck {});
-        // speciate
-        tree.trigger(Speciate {});
         let mut reevaluate = false;
         if runner.run_to {
-            if runner.generation < runner.requested_generation {
+            if runner.population.generation < runner.requested_generation {
                 reevaluate = true;
             } else {
-                runner.requested_generation = runner.generation + 1;
+                runner.requested_generation = runner.population.generation + 1;
                 runner.run_to = false;
             }
         }
@@ -1008,13 +968,17 @@ impl BestEvaluate {
     pub(crate) fn obs(
         trigger: Trigger<OnClick>,
         mut tree: Tree,
-        runner: Res<Runner>,
-        ids: Res<RunnerIds>,
+        mut runner: ResMut<Runner>,
+        views: Query<&GenomeView>,
     ) {
         // TODO for now replacing 0 slot => must be expanded view later
-        let entity = *runner.population.get(0).unwrap();
-        tree.entity(entity)
-            .insert(runner.best.clone().unwrap().0.clone());
+        let entity = *runner.slots.first().unwrap();
+        let index = views.get(entity).unwrap().index;
+        runner.population.genomes[index] = runner.best.clone().unwrap().0;
+        runner.networks[index] = Network::compile(
+            &runner.population.genomes[index],
+            &runner.population.environment,
+        );
         tree.trigger_targets(EvaluateGenome {}, entity);
     }
 }
@@ -1022,14 +986,10 @@ pub(crate) struct BestPrintGenome {}
 impl BestPrintGenome {
     pub(crate) fn obs(trigger: Trigger<OnClick>, mut tree: Tree, runner: Res<Runner>) {
         let best = runner.best.clone().unwrap().0;
-        for n in best.nodes.iter() {
-            println!("Node: {} ty: {:?}", n.id, n.ty);
-        }
-        for c in best.connections.iter() {
-            println!(
-                "Connection: {} from: {} to: {} weight: {} enabled: {}",
-                c.innovation, c.from, c.to, c.weight, c.enabled
-            );
+        let file = GenomeFile::new(best, &runner.population.environment);
+        println!("{}", file.to_json());
+        if let Err(e) = file.save("best-genome.json") {
+            println!("failed to write best-genome.json: {}", e);
         }
     }
 }
@@ -0,0 +1,98 @@
+use crate::neat::Fitness;
+use crate::snake::sim::RewardStatus;
+use crate::snake::{Evaluation, Reward};
+use serde::{Deserialize, Serialize};
+
+// turns a game into a fitness: step is added every turn, finish once at the end
+pub trait FitnessFunction: Send + Sync {
+    fn step(&self, status: RewardStatus) -> Fitness;
+    fn finish(&self, _evaluation: &Evaluation) -> Fitness {
+        0.0
+    }
+}
+// which FitnessFunction SnakeTask builds, weights come from the [snake] rewards
+#[derive(Copy, Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub enum FitnessKind {
+    // Reward::value, the original shaping
+    #[default]
+    Shaping,
+    // food_reward per food and nothing else
+    Food,
+    // food_reward per food plus survival for every turn alive
+    FoodSurvival {
+        survival: Fitness,
+    },
+    // steps + 2^food + 500 * food^2.1 once the game is over, 2^food turns linear past 10 food
+    Classic,
+}
+impl FitnessFunction for Reward {
+    fn step(&self, status: RewardStatus) -> Fitness {
+        let mut reward = *self;
+        reward.update(status);
+        reward.value()
+    }
+}
+pub struct FoodOnly {
+    pub food_reward: Fitness,
+}
+impl FitnessFunction for FoodOnly {
+    fn step(&self, status: RewardStatus) -> Fitness {
+        f32::from(status.collected_food) * self.food_reward
+    }
+}
+pub struct FoodSurvival {
+    pub food_reward: Fitness,
+    pub survival: Fitness,
+}
+impl FitnessFunction for FoodSurvival {
+    fn step(&self, status: RewardStatus) -> Fitness {
+        f32::from(status.collected_food) * self.food_reward + self.survival
+    }
+}
+pub struct Classic {}
+impl Classic {
+    // past this 2^food grows by 2^10 per food: uncapped it is inf from 128 food on, and
+    // much above 2^20 an f32 can no longer tell the steps or the polynomial term apart
+    pub const MAX_EXPONENT: f64 = 10.0;
+}
+impl FitnessFunction for Classic {
+    fn step(&self, _status: RewardStatus) -> Fitness {
+        0.0
+    }
+    fn finish(&self, evaluation: &Evaluation) -> Fitness {
+        let food = evaluation.total_food_collected as f64;
+        let exponential = if food < Self::MAX_EXPONENT {
+            2f64.powf(food)
+        } else {
+            2f64.powf(Self::MAX_EXPONENT) * (food - Self::MAX_EXPONENT + 1.0)
+        };
+        (evaluation.num_turns_taken as f64 + exponential + 500.0 * food.powf(2.1)) as Fitness
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_stays_finite_and_increasing() {
+        let classic = Classic {};
+        let game = |food: i32, turns: i32| {
+            classic.finish(&Evaluation {
+                total_food_collected: food,
+                num_turns_taken: turns,
+                ..Evaluation::default()
+            })
+        };
+        let score = |food: i32| game(food, 10_000);
+        assert_eq!(score(0), 10_001.0);
+        assert!(score(899).is_finite());
+        assert!(score(9) < score(10));
+        assert!(score(10) < score(11));
+        assert!(score(50) < score(51));
+        assert!(score(51) < score(100));
+        assert!(score(200) < score(899));
+        // at equal food above the cap the longer game still ranks higher
+        assert!(game(100, 1_000) < game(100, 2_000));
+        assert!(game(899, 1_000) < game(899, 5_000));
+    }
+}
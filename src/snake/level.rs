@@ -0,0 +1,130 @@
+use crate::snake::sim::{Direction, GameGrid, Location};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+// a board drawn as text, one character per cell:
+//   # wall   . empty   > < ^ v head of the starting snake facing that way   F first food
+// every row must be as wide as the first, the rows set the grid size
+#[derive(Clone)]
+pub struct Level {
+    pub grid: GameGrid,
+    pub walls: HashSet<Location>,
+    pub start: Option<(Location, Direction)>,
+    pub food: Option<Location>,
+}
+impl Level {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LevelError> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+    pub fn parse(text: &str) -> Result<Self, LevelError> {
+        let rows = text
+            .lines()
+            .map(|l| l.trim_end())
+            .filter(|l| !l.is_empty())
+            .collect::<Vec<_>>();
+        let width = rows.first().map(|r| r.chars().count()).unwrap_or_default();
+        if width == 0 {
+            return Err(LevelError::Invalid("level has no rows".to_string()));
+        }
+        let mut level = Self {
+            grid: GameGrid::new(width as i32, rows.len() as i32),
+            walls: HashSet::new(),
+            start: None,
+            food: None,
+        };
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(LevelError::Invalid(format!(
+                    "row {} is {} cells wide but the first row is {}",
+                    y + 1,
+                    row.chars().count(),
+                    width
+                )));
+            }
+            for (x, cell) in row.chars().enumerate() {
+                let location = Location::new(x as i32, y as i32);
+                let heading = match cell {
+                    '#' => {
+                        level.walls.insert(location);
+                        continue;
+                    }
+                    '.' => continue,
+                    'F' if level.food.is_none() => {
+                        level.food = Some(location);
+                        continue;
+                    }
+                    'F' => return Err(LevelError::Invalid("more than one F".to_string())),
+                    '>' => Direction::Right,
+                    '<' => Direction::Left,
+                    '^' => Direction::Up,
+                    'v' => Direction::Down,
+                    _ => {
+                        return Err(LevelError::Invalid(format!(
+                            "unknown cell {:?} at row {} column {}",
+                            cell,
+                            y + 1,
+                            x + 1
+                        )))
+                    }
+                };
+                if level.start.replace((location, heading)).is_some() {
+                    return Err(LevelError::Invalid(
+                        "more than one starting head".to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(level)
+    }
+}
+#[derive(Debug)]
+pub enum LevelError {
+    Io(std::io::Error),
+    Invalid(String),
+}
+impl Display for LevelError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LevelError::Io(e) => write!(f, "level io: {}", e),
+            LevelError::Invalid(reason) => write!(f, "invalid level: {}", reason),
+        }
+    }
+}
+impl std::error::Error for LevelError {}
+impl From<std::io::Error> for LevelError {
+    fn from(e: std::io::Error) -> Self {
+        LevelError::Io(e)
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_reads_walls_head_and_food() {
+        let level = Level::parse("#####\n#>.F#\n#####\n").unwrap();
+        assert_eq!(level.grid.grid, (5, 3));
+        assert_eq!(level.walls.len(), 12);
+        assert!(level.walls.contains(&Location::new(0, 1)));
+        assert_eq!(level.start, Some((Location::new(1, 1), Direction::Right)));
+        assert_eq!(level.food, Some(Location::new(3, 1)));
+    }
+    #[test]
+    fn markers_are_optional() {
+        let level = Level::parse("...\n.#.\n").unwrap();
+        assert_eq!(level.start, None);
+        assert_eq!(level.food, None);
+        assert_eq!(level.walls, HashSet::from([Location::new(1, 1)]));
+    }
+    #[test]
+    fn parse_rejects_malformed_boards() {
+        for text in ["", "\n\n", "...\n..\n", "..x\n", "F.F\n", ">.<\n"] {
+            assert!(
+                matches!(Level::parse(text), Err(LevelError::Invalid(_))),
+                "{:?}",
+                text
+            );
+        }
+    }
+}
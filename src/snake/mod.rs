@@ -0,0 +1,525 @@
+use crate::neat::environment::Environment;
+use crate::neat::network::Network;
+use crate::neat::task::{Metrics, Outcome, Task};
+use crate::neat::{Fitness, GameRng};
+use fitness::{Classic, FitnessFunction, FitnessKind, FoodOnly, FoodSurvival};
+#[cfg(feature = "ui")]
+use foliage::bevy_ecs;
+#[cfg(feature = "ui")]
+use foliage::bevy_ecs::component::Component;
+use serde::{Deserialize, Serialize};
+use sim::{Action, Death, Direction, GameGrid, Limits, Location, RewardStatus, SnakeSim, Start};
+use std::collections::HashSet;
+
+pub mod fitness;
+pub mod level;
+pub mod sim;
+
+pub fn environment() -> Environment {
+    let mut environment = Environment::new();
+    environment.population_count = 150;
+    environment.input_size = Observation::default().channels();
+    environment.output_size = ActionEncoding::default().outputs();
+    environment.compatibility_factors.c1 = 1.0;
+    environment.compatibility_factors.c2 = 1.0;
+    environment.compatibility_factors.c3 = 0.4;
+    environment.compatibility_threshold = 3.0;
+    environment.stagnation_threshold = 15;
+    environment.elitism = 0.2;
+    environment.add_connection = 0.2;
+    environment.add_node = 0.07;
+    environment.inherit_disable = 0.75;
+    environment.only_mutate = 0.25;
+    environment.crossover_only = 0.2;
+    environment.connection_weight = 0.8;
+    environment.perturb = 0.9;
+    environment.max_turns = 5000;
+    environment
+}
+pub fn reward() -> Reward {
+    Reward::new(5.0, 1.75, 0.75)
+}
+pub fn game_grid() -> GameGrid {
+    GameGrid::new(30, 30)
+}
+// off by default, games only end on a collision or max_turns
+pub fn limits() -> Limits {
+    Limits::default()
+}
+pub fn evaluate(
+    network: &Network,
+    environment: &Environment,
+    task: &SnakeTask,
+    rng: GameRng,
+) -> Evaluation {
+    let mut sim = task.sim(environment, rng);
+    let fitness = task.fitness_function();
+    let mut evaluation = Evaluation::default();
+    let mut activations = network.activations();
+    loop {
+        let input = sim.observe();
+        network.activate(&mut activations, &input.channels);
+        let output = NetworkOutput::decode(
+            activations.outputs(environment),
+            task.actions,
+            sim.direction,
+        );
+        let action = output.action();
+        let step = sim.step(action);
+        evaluation.record(action, step.status, fitness.as_ref());
+        if let Some(death) = evaluation.ended(step.death, environment) {
+            evaluation.end(death, sim.segments.len() as i32, fitness.as_ref());
+            return evaluation;
+        }
+    }
+}
+// everything about the game that is not part of the NEAT environment
+#[derive(Clone)]
+pub struct SnakeTask {
+    pub grid: GameGrid,
+    // empty unless a level is loaded
+    pub walls: HashSet<Location>,
+    pub start: Start,
+    pub limits: Limits,
+    pub observation: Observation,
+    pub actions: ActionEncoding,
+    pub fitness: FitnessKind,
+    // weights for Shaping, food_collection_reward also for Food / FoodSurvival
+    pub reward: Reward,
+}
+impl Default for SnakeTask {
+    fn default() -> Self {
+        Self {
+            grid: game_grid(),
+            walls: HashSet::new(),
+            start: Start::default(),
+            limits: limits(),
+            observation: Observation::default(),
+            actions: ActionEncoding::default(),
+            fitness: FitnessKind::default(),
+            reward: reward(),
+        }
+    }
+}
+impl SnakeTask {
+    pub fn sim(&self, environment: &Environment, rng: GameRng) -> SnakeSim {
+        let mut start = self.start;
+        start.random_food |= environment.shared_food;
+        start.shared_food = environment.shared_food;
+        let mut sim = SnakeSim::with_walls(self.grid, self.walls.clone(), start, rng);
+        sim.limits = self.limits;
+        sim.observation = self.observation;
+        sim
+    }
+    pub fn fitness_function(&self) -> Box<dyn FitnessFunction> {
+        let food_reward = self.reward.food_collection_reward;
+        match self.fitness {
+            FitnessKind::Shaping => Box::new(self.reward),
+            FitnessKind::Food => Box::new(FoodOnly { food_reward }),
+            FitnessKind::FoodSurvival { survival } => Box::new(FoodSurvival {
+                food_reward,
+                survival,
+            }),
+            FitnessKind::Classic => Box::new(Classic {}),
+        }
+    }
+}
+impl Task for SnakeTask {
+    fn name(&self) -> &str {
+        "snake"
+    }
+    fn input_size(&self) -> usize {
+        self.observation.channels()
+    }
+    fn output_size(&self) -> usize {
+        self.actions.outputs()
+    }
+    fn evaluate(&self, network: &Network, environment: &Environment, rng: &mut GameRng) -> Outcome {
+        evaluate(network, environment, self, rng.clone()).outcome()
+    }
+    fn metrics(&self) -> Vec<&'static str> {
+        Evaluation::default().metrics().into_keys().collect()
+    }
+    fn environment(&self) -> Environment {
+        let mut environment = environment();
+        environment.input_size = self.input_size();
+        environment.output_size = self.output_size();
+        environment
+    }
+}
+#[cfg_attr(feature = "ui", derive(Component))]
+#[derive(Copy, Clone)]
+pub struct Reward {
+    pub can_move_towards_food: bool,
+    pub moved_towards_food: bool,
+    pub collected_food: bool,
+    pub food_collection_reward: Fitness,
+    pub towards_food_reward: Fitness,
+    pub can_move_towards_food_reward: Fitness,
+}
+impl Reward {
+    pub fn value(&self) -> f32 {
+        let conditional = if !self.moved_towards_food && self.can_move_towards_food {
+            -1.0
+        } else if !self.can_move_towards_food && !self.moved_towards_food {
+            1.0
+        } else {
+            0.0
+        };
+        f32::from(self.collected_food) * self.food_collection_reward
+            + f32::from(self.moved_towards_food) * self.towards_food_reward
+            + self.can_move_towards_food_reward * conditional
+    }
+    pub fn new(fc: Fitness, tf: Fitness, cmtf: Fitness) -> Self {
+        Self {
+            can_move_towards_food: false,
+            moved_towards_food: false,
+            collected_food: false,
+            food_collection_reward: fc,
+            towards_food_reward: tf,
+            can_move_towards_food_reward: cmtf,
+        }
+    }
+    pub fn update(&mut self, status: RewardStatus) {
+        self.can_move_towards_food = status.can_move_towards_food;
+        self.moved_towards_food = status.moved_towards_food;
+        self.collected_food = status.collected_food;
+    }
+}
+// fitness + counters are for the game in progress, finished games move into trials
+#[cfg_attr(feature = "ui", derive(Component))]
+#[derive(Clone, Default)]
+pub struct Evaluation {
+    pub fitness: Fitness,
+    pub total_food_collected: i32,
+    pub num_turns_taken: i32,
+    pub moves_towards_food: i32,
+    // indexed by Action: forward, left, right
+    pub actions: [i32; 3],
+    pub length: i32,
+    pub death: Option<Death>,
+    pub trials: Vec<Outcome>,
+}
+impl Evaluation {
+    pub fn record(&mut self, action: Action, status: RewardStatus, fitness: &dyn FitnessFunction) {
+        self.total_food_collected += i32::from(status.collected_food);
+        self.moves_towards_food += i32::from(status.moved_towards_food || status.collected_food);
+        self.actions[action as usize] += 1;
+        self.num_turns_taken += 1;
+        self.fitness += fitness.step(status);
+    }
+    // death from the last step, or the turn limit once max_turns are used up
+    pub fn ended(&self, death: Option<Death>, environment: &Environment) -> Option<Death> {
+        death.or((self.num_turns_taken >= environment.max_turns).then_some(Death::TurnLimit))
+    }
+    pub fn end(&mut self, death: Death, length: i32, fitness: &dyn FitnessFunction) {
+        self.death = Some(death);
+        self.length = length;
+        self.fitness += fitness.finish(self);
+    }
+    pub fn finish_trial(&mut self) {
+        self.trials.push(self.outcome());
+        *self = Self {
+            trials: std::mem::take(&mut self.trials),
+            ..Self::default()
+        };
+    }
+    pub fn aggregate(&self, environment: &Environment) -> Outcome {
+        environment
+            .aggregation
+            .aggregate(&self.trials, environment.stddev_penalty)
+    }
+    pub fn outcome(&self) -> Outcome {
+        Outcome {
+            fitness: self.fitness,
+            collected: self.total_food_collected as f32,
+            steps: self.num_turns_taken as f32,
+            metrics: self.metrics(),
+        }
+    }
+    pub fn metrics(&self) -> Metrics {
+        let turns = self.num_turns_taken.max(1) as f32;
+        let mut metrics = Death::ALL
+            .iter()
+            .map(|d| (d.metric(), f32::from(self.death == Some(*d))))
+            .collect::<Metrics>();
+        metrics.insert("length", self.length as f32);
+        // a game without food counts all its turns
+        metrics.insert(
+            "steps_per_food",
+            self.num_turns_taken as f32 / self.total_food_collected.max(1) as f32,
+        );
+        metrics.insert("towards_food", self.moves_towards_food as f32 / turns);
+        metrics.insert(
+            "forward",
+            self.actions[Action::Forward as usize] as f32 / turns,
+        );
+        metrics.insert("left", self.actions[Action::Left as usize] as f32 / turns);
+        metrics.insert("right", self.actions[Action::Right as usize] as f32 / turns);
+        metrics
+    }
+}
+// what the network sees every turn, sizes Environment::input_size
+#[derive(Copy, Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Observation {
+    // free / food one cell left, right and forward
+    #[default]
+    Neighbours,
+    // 8 rays relative to the heading: inverse distance to wall, body and food
+    Rays,
+    // size x size cells around the head, rotated so forward is up; each cell is
+    // wall / body / food one-hot or a single scalar (-1 / -0.5 / 1, empty 0)
+    Window {
+        size: i32,
+        one_hot: bool,
+    },
+}
+impl Observation {
+    pub fn channels(self) -> usize {
+        match self {
+            Observation::Neighbours => Neighbours::CHANNELS,
+            Observation::Rays => sim::RAYS.len() * 3,
+            Observation::Window { size, one_hot } => {
+                (size * size) as usize * if one_hot { 3 } else { 1 }
+            }
+        }
+    }
+}
+#[cfg_attr(feature = "ui", derive(Component))]
+#[derive(Clone, Default)]
+pub struct NetworkInput {
+    pub channels: Vec<f32>,
+}
+#[derive(Copy, Clone, Default)]
+pub struct Neighbours {
+    pub can_move_left: bool,
+    pub can_move_right: bool,
+    pub can_move_forward: bool,
+    pub is_food_left: bool,
+    pub is_food_right: bool,
+    pub is_food_forward: bool,
+}
+impl Neighbours {
+    pub const CHANNELS: usize = 6;
+    pub fn get_channel(&self, i: usize) -> f32 {
+        match i {
+            0 => f32::from(self.can_move_left),
+            1 => f32::from(self.can_move_right),
+            2 => f32::from(self.can_move_forward),
+            3 => f32::from(self.is_food_left),
+            4 => f32::from(self.is_food_right),
+            5 => f32::from(self.is_food_forward),
+            _ => panic!("no-channel"),
+        }
+    }
+    pub fn channels(&self) -> Vec<f32> {
+        (0..Self::CHANNELS).map(|i| self.get_channel(i)).collect()
+    }
+}
+// how output activations become an Action, sizes Environment::output_size
+#[derive(Copy, Clone, Default, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ActionEncoding {
+    // left / right each fire above 0.5, left wins ties, neither means forward
+    #[default]
+    Threshold,
+    // argmax over left / forward / right
+    Relative,
+    // argmax over up / down / left / right, ignoring the way back into the body
+    Absolute,
+}
+impl ActionEncoding {
+    pub fn outputs(self) -> usize {
+        match self {
+            ActionEncoding::Threshold => 2,
+            ActionEncoding::Relative => 3,
+            ActionEncoding::Absolute => 4,
+        }
+    }
+}
+#[cfg_attr(feature = "ui", derive(Component))]
+#[derive(Default, Debug)]
+pub struct NetworkOutput {
+    pub action: Action,
+}
+impl NetworkOutput {
+    // heading is needed to turn an absolute direction into a relative action
+    pub fn decode(outputs: &[f32], encoding: ActionEncoding, heading: Direction) -> Self {
+        let action = match encoding {
+            ActionEncoding::Threshold => {
+                if outputs[0] > 0.5 {
+                    Action::Left
+                } else if outputs[1] > 0.5 {
+                    Action::Right
+                } else {
+                    Action::Forward
+                }
+            }
+            ActionEncoding::Relative => {
+                let actions = [Action::Left, Action::Forward, Action::Right];
+                actions[argmax(outputs.iter().copied().enumerate())]
+            }
+            ActionEncoding::Absolute => {
+                let directions = [
+                    Direction::Up,
+                    Direction::Down,
+                    Direction::Left,
+                    Direction::Right,
+                ];
+                let allowed = outputs
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .filter(|(i, _)| directions[*i] != heading.left().left());
+                let direction = directions[argmax(allowed)];
+                if direction == heading.left() {
+                    Action::Left
+                } else if direction == heading.right() {
+                    Action::Right
+                } else {
+                    Action::Forward
+                }
+            }
+        };
+        Self { action }
+    }
+    pub fn action(&self) -> Action {
+        self.action
+    }
+}
+// first index wins ties, starting from the first value so a filtered out index is never returned
+fn argmax(mut values: impl Iterator<Item = (usize, f32)>) -> usize {
+    let Some(first) = values.next() else {
+        return 0;
+    };
+    values
+        .fold(first, |best, (i, v)| if v > best.1 { (i, v) } else { best })
+        .0
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metrics_are_fractions_of_the_game() {
+        let evaluation = Evaluation {
+            total_food_collected: 4,
+            num_turns_taken: 40,
+            moves_towards_food: 30,
+            actions: [20, 12, 8],
+            length: 10,
+            death: Some(Death::Loop),
+            ..Evaluation::default()
+        };
+        let metrics = evaluation.metrics();
+        assert_eq!(metrics["death_loop"], 1.0);
+        for death in [
+            Death::Wall,
+            Death::Body,
+            Death::Starvation,
+            Death::TurnLimit,
+            Death::Full,
+        ] {
+            assert_eq!(metrics[death.metric()], 0.0);
+        }
+        assert_eq!(metrics["length"], 10.0);
+        assert_eq!(metrics["steps_per_food"], 10.0);
+        assert_eq!(metrics["towards_food"], 0.75);
+        assert_eq!(metrics["forward"], 0.5);
+        assert_eq!(metrics["left"], 0.3);
+        assert_eq!(metrics["right"], 0.2);
+    }
+    #[test]
+    fn a_game_without_food_counts_all_its_turns() {
+        let evaluation = Evaluation {
+            num_turns_taken: 25,
+            death: Some(Death::Starvation),
+            ..Evaluation::default()
+        };
+        assert_eq!(evaluation.metrics()["steps_per_food"], 25.0);
+        // nothing played at all still divides by one turn
+        let metrics = Evaluation::default().metrics();
+        assert_eq!(metrics["towards_food"], 0.0);
+        assert!(Death::ALL.iter().all(|d| metrics[d.metric()] == 0.0));
+    }
+    fn decode(outputs: &[f32], encoding: ActionEncoding, heading: Direction) -> Action {
+        NetworkOutput::decode(outputs, encoding, heading).action()
+    }
+    #[test]
+    fn threshold_prefers_left_then_right() {
+        let threshold = ActionEncoding::Threshold;
+        assert_eq!(decode(&[0.6, 0.9], threshold, Direction::Up), Action::Left);
+        assert_eq!(decode(&[0.4, 0.9], threshold, Direction::Up), Action::Right);
+        assert_eq!(
+            decode(&[0.5, 0.5], threshold, Direction::Up),
+            Action::Forward
+        );
+    }
+    #[test]
+    fn relative_takes_the_largest_output() {
+        let relative = ActionEncoding::Relative;
+        assert_eq!(
+            decode(&[0.1, 0.9, 0.2], relative, Direction::Up),
+            Action::Forward
+        );
+        assert_eq!(
+            decode(&[0.1, 0.2, 0.8], relative, Direction::Up),
+            Action::Right
+        );
+        // ties go to the first output, left
+        assert_eq!(
+            decode(&[0.7, 0.7, 0.7], relative, Direction::Up),
+            Action::Left
+        );
+        assert_eq!(
+            decode(&[0.1, 0.7, 0.7], relative, Direction::Up),
+            Action::Forward
+        );
+    }
+    #[test]
+    fn absolute_turns_directions_into_actions() {
+        let absolute = ActionEncoding::Absolute;
+        // outputs are up, down, left, right
+        assert_eq!(
+            decode(&[0.9, 0.1, 0.2, 0.3], absolute, Direction::Right),
+            Action::Left
+        );
+        assert_eq!(
+            decode(&[0.1, 0.9, 0.2, 0.3], absolute, Direction::Right),
+            Action::Right
+        );
+        assert_eq!(
+            decode(&[0.1, 0.2, 0.3, 0.9], absolute, Direction::Right),
+            Action::Forward
+        );
+    }
+    #[test]
+    fn absolute_never_reverses() {
+        let absolute = ActionEncoding::Absolute;
+        // left is straight back when heading right, the best of the rest wins
+        assert_eq!(
+            decode(&[0.1, 0.5, 1.0, 0.3], absolute, Direction::Right),
+            Action::Right
+        );
+        // up is straight back when heading down: ties go to down, then left / right
+        assert_eq!(
+            decode(&[0.5, 0.5, 0.5, 0.5], absolute, Direction::Down),
+            Action::Forward
+        );
+        assert_eq!(
+            decode(
+                &[1.0, f32::MIN, f32::MIN, f32::MIN],
+                absolute,
+                Direction::Down
+            ),
+            Action::Forward
+        );
+        assert_eq!(
+            decode(
+                &[1.0, f32::NAN, f32::NAN, f32::NAN],
+                absolute,
+                Direction::Down
+            ),
+            Action::Forward
+        );
+    }
+}
@@ -0,0 +1,1031 @@
+use crate::neat::GameRng;
+use crate::snake::{Neighbours, NetworkInput, Observation};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+#[derive(Copy, Clone)]
+pub struct RewardStatus {
+    pub can_move_towards_food: bool,
+    pub moved_towards_food: bool,
+    pub collected_food: bool,
+}
+#[derive(Copy, Clone)]
+pub struct GameGrid {
+    pub grid: (i32, i32),
+    // leaving one edge re-enters on the opposite side instead of hitting a wall
+    pub wrap: bool,
+}
+
+impl GameGrid {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self {
+            grid: (x, y),
+            wrap: false,
+        }
+    }
+    pub fn contains(&self, location: Location) -> bool {
+        location.x >= 0 && location.x < self.grid.0 && location.y >= 0 && location.y < self.grid.1
+    }
+    // back onto the board when wrapping, untouched otherwise
+    pub fn wrap(&self, location: Location) -> Location {
+        if !self.wrap {
+            return location;
+        }
+        Location::new(
+            location.x.rem_euclid(self.grid.0),
+            location.y.rem_euclid(self.grid.1),
+        )
+    }
+    pub fn step(&self, location: Location, direction: Direction) -> Location {
+        self.wrap(location.step(direction))
+    }
+    // from a to b, the shorter way around when wrapping
+    pub fn delta(&self, a: Location, b: Location) -> (i32, i32) {
+        let (mut dx, mut dy) = (b.x - a.x, b.y - a.y);
+        if self.wrap {
+            let shortest = |d: i32, size: i32| {
+                let d = d.rem_euclid(size);
+                if d > size / 2 {
+                    d - size
+                } else {
+                    d
+                }
+            };
+            dx = shortest(dx, self.grid.0);
+            dy = shortest(dy, self.grid.1);
+        }
+        (dx, dy)
+    }
+    pub fn distance(&self, a: Location, b: Location) -> f32 {
+        let (dx, dy) = self.delta(a, b);
+        ((dx as f32).powi(2) + (dy as f32).powi(2)).sqrt()
+    }
+}
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+pub enum Action {
+    #[default]
+    Forward,
+    Left,
+    Right,
+}
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Death {
+    // the grid edge or a level wall
+    Wall,
+    Body,
+    Starvation,
+    Loop,
+    // never produced by step, the caller ends games at environment.max_turns
+    TurnLimit,
+    // the snake ate with no free cell left for the next food, the game is won
+    Full,
+}
+impl Death {
+    pub const ALL: [Death; 6] = [
+        Death::Wall,
+        Death::Body,
+        Death::Starvation,
+        Death::Loop,
+        Death::TurnLimit,
+        Death::Full,
+    ];
+    pub fn metric(self) -> &'static str {
+        match self {
+            Death::Wall => "death_wall",
+            Death::Body => "death_body",
+            Death::Starvation => "death_starvation",
+            Death::Loop => "death_loop",
+            Death::TurnLimit => "death_turn_limit",
+            Death::Full => "death_full",
+        }
+    }
+}
+// ways to end a game that is going nowhere, zero / false switches each off
+#[derive(Copy, Clone, Default)]
+pub struct Limits {
+    // turns allowed between two foods, plus per_segment for every segment of the snake
+    pub starvation: i32,
+    pub starvation_per_segment: i32,
+    // a repeated (head, direction, body) state since the last food ends the game
+    pub detect_loops: bool,
+}
+#[derive(Copy, Clone)]
+pub struct StepOutcome {
+    pub status: RewardStatus,
+    pub death: Option<Death>,
+}
+impl StepOutcome {
+    pub fn alive(&self) -> bool {
+        self.death.is_none()
+    }
+}
+// how every game opens, the body trails straight behind the head
+#[derive(Copy, Clone, Debug)]
+pub struct Start {
+    pub length: i32,
+    // None keeps the classic spot 5 cells left of centre
+    pub position: Option<Location>,
+    pub heading: Direction,
+    // position + heading drawn from the game's rng, so every trial differs
+    pub random: bool,
+    // first food from rng instead of 10 cells ahead of the head
+    pub random_food: bool,
+    // fixed first food, None puts it 10 cells ahead of the head
+    pub food: Option<Location>,
+    // every food comes from the shared sequence, see SnakeSim::spawn_food
+    pub shared_food: bool,
+}
+impl Default for Start {
+    fn default() -> Self {
+        Self {
+            length: 6,
+            position: None,
+            heading: Direction::Right,
+            random: false,
+            random_food: false,
+            food: None,
+            shared_food: false,
+        }
+    }
+}
+impl Start {
+    pub const FOOD_AHEAD: i32 = 10;
+    fn position(&self, grid: GameGrid) -> Location {
+        self.position
+            .unwrap_or(Location::new(grid.grid.0 / 2 - 5, grid.grid.1 / 2))
+    }
+    fn body(grid: GameGrid, head: Location, heading: Direction, length: i32) -> Vec<Location> {
+        let back = heading.left().left();
+        let mut body = vec![grid.wrap(head)];
+        for _ in 1..length {
+            body.push(grid.step(*body.last().unwrap(), back));
+        }
+        body
+    }
+    // whether the starting snake can be placed on grid at all
+    pub fn fits(&self, grid: GameGrid, walls: &HashSet<Location>) -> bool {
+        if self.length < 1 {
+            return false;
+        }
+        if self.random {
+            return !self.placements(grid, walls).is_empty();
+        }
+        // a wrapped body longer than the board would run into itself
+        let body = Self::body(grid, self.position(grid), self.heading, self.length);
+        body.iter().all(|l| grid.contains(*l) && !walls.contains(l))
+            && body.iter().collect::<HashSet<_>>().len() == body.len()
+    }
+    // every (head, heading) a random start could draw whose body stays on the grid and off the walls
+    fn placements(&self, grid: GameGrid, walls: &HashSet<Location>) -> Vec<(Location, Direction)> {
+        let (width, height) = grid.grid;
+        let mut placements = vec![];
+        for heading in [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ] {
+            let back = heading.left().left();
+            for y in 0..height {
+                for x in 0..width {
+                    let head = Location::new(x, y);
+                    let mut cell = head;
+                    let mut clear = true;
+                    for _ in 0..self.length {
+                        if !grid.contains(cell) || walls.contains(&cell) {
+                            clear = false;
+                            break;
+                        }
+                        cell = cell.step(back);
+                    }
+                    if clear {
+                        placements.push((head, heading));
+                    }
+                }
+            }
+        }
+        placements
+    }
+    // whether a fixed first food lies on the grid, off the walls and off the fixed starting snake
+    pub fn food_fits(&self, grid: GameGrid, walls: &HashSet<Location>) -> bool {
+        let Some(food) = self.food else {
+            return true;
+        };
+        grid.contains(food)
+            && !walls.contains(&food)
+            && !Self::body(grid, self.position(grid), self.heading, self.length).contains(&food)
+    }
+}
+// (forward, right) steps of each ray, clockwise from straight ahead
+pub const RAYS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+// the whole game without any rendering, the app only mirrors segments + food into panels
+#[derive(Clone)]
+pub struct SnakeSim {
+    pub segments: Vec<Location>,
+    // where the head was before the last step, a one segment snake has no neck to ask
+    pub last_head: Location,
+    pub direction: Direction,
+    pub food: Location,
+    pub grid: GameGrid,
+    // cells inside the grid that act like its edge
+    pub walls: HashSet<Location>,
+    pub collected_food: bool,
+    pub can_move_towards_food: bool,
+    pub death: Option<Death>,
+    pub limits: Limits,
+    pub observation: Observation,
+    pub turns_since_food: i32,
+    // foods spawned since the reset, numbers the shared sequence
+    pub foods: u32,
+    pub seen: HashSet<u64>,
+    pub start: Start,
+    pub rng: GameRng,
+}
+impl SnakeSim {
+    // room for rejected draws before the next food's spot in the shared stream
+    const WORDS_PER_FOOD: u128 = 16;
+    // random draws before a start falls back to listing every placement that clears the walls
+    const RANDOM_START_DRAWS: usize = 100;
+    pub fn new(grid: GameGrid, start: Start, rng: GameRng) -> Self {
+        Self::with_walls(grid, HashSet::new(), start, rng)
+    }
+    pub fn with_walls(
+        grid: GameGrid,
+        walls: HashSet<Location>,
+        start: Start,
+        rng: GameRng,
+    ) -> Self {
+        let mut sim = Self {
+            segments: vec![],
+            last_head: Location::default(),
+            direction: Direction::Right,
+            food: Location::default(),
+            grid,
+            walls,
+            collected_food: false,
+            can_move_towards_food: false,
+            death: None,
+            limits: Limits::default(),
+            observation: Observation::default(),
+            turns_since_food: 0,
+            foods: 0,
+            seen: HashSet::new(),
+            start,
+            rng: rng.clone(),
+        };
+        sim.reset(rng);
+        sim
+    }
+    // back to the starting position, food respawns draw from rng from here on
+    pub fn reset(&mut self, rng: GameRng) {
+        self.rng = rng;
+        let start = self.start;
+        let (head, heading) = if start.random {
+            self.random_start()
+        } else {
+            (start.position(self.grid), start.heading)
+        };
+        self.segments = Start::body(self.grid, head, heading, start.length);
+        self.last_head = self.head();
+        self.direction = heading;
+        let food = start.food.unwrap_or_else(|| {
+            let mut food = head;
+            for _ in 0..Start::FOOD_AHEAD {
+                food = self.grid.step(food, heading);
+            }
+            food
+        });
+        self.food = food;
+        self.collected_food = false;
+        self.can_move_towards_food = false;
+        self.death = None;
+        self.turns_since_food = 0;
+        self.foods = 0;
+        self.seen.clear();
+        if start.random || start.random_food || self.is_wall(food) {
+            self.spawn_food();
+        }
+    }
+    // redraws until the body misses every wall, on a crowded board it picks among the placements
+    // that do, and with none at all (Start::fits is false) it keeps the fixed start
+    fn random_start(&mut self) -> (Location, Direction) {
+        for _ in 0..Self::RANDOM_START_DRAWS {
+            let Some((head, heading)) = self.random_placement() else {
+                break;
+            };
+            if Start::body(self.grid, head, heading, self.start.length)
+                .iter()
+                .all(|l| !self.walls.contains(l))
+            {
+                return (head, heading);
+            }
+        }
+        let placements = self.start.placements(self.grid, &self.walls);
+        if placements.is_empty() {
+            return (self.start.position(self.grid), self.start.heading);
+        }
+        placements[self.rng.gen_range(0..placements.len())]
+    }
+    // None when the snake is longer than the board is wide and high
+    fn random_placement(&mut self) -> Option<(Location, Direction)> {
+        let (width, height) = self.grid.grid;
+        let length = self.start.length;
+        let headings = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Left,
+            Direction::Right,
+        ]
+        .into_iter()
+        .filter(|d| match d {
+            Direction::Left | Direction::Right => length <= width,
+            Direction::Up | Direction::Down => length <= height,
+        })
+        .collect::<Vec<_>>();
+        if headings.is_empty() {
+            return None;
+        }
+        let heading = headings[self.rng.gen_range(0..headings.len())];
+        // the head sits far enough from the wall behind it for the whole body
+        let tail = length - 1;
+        let (x, y) = match heading {
+            Direction::Right => (
+                self.rng.gen_range(tail..width),
+                self.rng.gen_range(0..height),
+            ),
+            Direction::Left => (
+                self.rng.gen_range(0..width - tail),
+                self.rng.gen_range(0..height),
+            ),
+            Direction::Down => (
+                self.rng.gen_range(0..width),
+                self.rng.gen_range(tail..height),
+            ),
+            Direction::Up => (
+                self.rng.gen_range(0..width),
+                self.rng.gen_range(0..height - tail),
+            ),
+        };
+        Some((Location::new(x, y), heading))
+    }
+    pub fn head(&self) -> Location {
+        *self.segments.first().unwrap()
+    }
+    pub fn is_wall(&self, location: Location) -> bool {
+        !self.grid.contains(location) || self.walls.contains(&location)
+    }
+    fn is_free(&self, location: Location) -> bool {
+        !self.is_wall(location) && !self.segments.contains(&location)
+    }
+    fn is_food_towards(&self, direction: Direction) -> bool {
+        let (fx, fy) = self.grid.delta(self.head(), self.food);
+        let (dx, dy) = direction.offset();
+        fx * dx + fy * dy > 0
+    }
+    pub fn observe(&self) -> NetworkInput {
+        let channels = match self.observation {
+            Observation::Neighbours => self.neighbours().channels(),
+            Observation::Rays => self.rays(),
+            Observation::Window { size, one_hot } => self.window(size, one_hot),
+        };
+        NetworkInput { channels }
+    }
+    pub fn neighbours(&self) -> Neighbours {
+        let head = self.head();
+        Neighbours {
+            can_move_left: self.is_free(self.grid.step(head, self.direction.left())),
+            can_move_right: self.is_free(self.grid.step(head, self.direction.right())),
+            can_move_forward: self.is_free(self.grid.step(head, self.direction)),
+            is_food_left: self.is_food_towards(self.direction.left()),
+            is_food_right: self.is_food_towards(self.direction.right()),
+            is_food_forward: self.is_food_towards(self.direction),
+        }
+    }
+    fn rays(&self) -> Vec<f32> {
+        let head = self.head();
+        let (fx, fy) = self.direction.offset();
+        let (rx, ry) = self.direction.right().offset();
+        // a wrapped ray stops after going once around the board and sees no wall
+        let reach = self.grid.grid.0.max(self.grid.grid.1);
+        let mut channels = Vec::with_capacity(RAYS.len() * 3);
+        for (forward, right) in RAYS {
+            let (dx, dy) = (forward * fx + right * rx, forward * fy + right * ry);
+            let mut location = head;
+            let mut distance = 0;
+            let mut wall = 0.0;
+            let mut body = 0.0;
+            let mut food = 0.0;
+            loop {
+                location = self
+                    .grid
+                    .wrap(Location::new(location.x + dx, location.y + dy));
+                distance += 1;
+                if self.is_wall(location) {
+                    wall = 1.0 / distance as f32;
+                    break;
+                }
+                if self.grid.wrap && distance >= reach {
+                    break;
+                }
+                if body == 0.0 && self.segments.contains(&location) {
+                    body = 1.0 / distance as f32;
+                }
+                if food == 0.0 && location == self.food {
+                    food = 1.0 / distance as f32;
+                }
+            }
+            channels.extend([wall, body, food]);
+        }
+        channels
+    }
+    fn window(&self, size: i32, one_hot: bool) -> Vec<f32> {
+        let head = self.head();
+        let (fx, fy) = self.direction.offset();
+        let (rx, ry) = self.direction.right().offset();
+        let half = size / 2;
+        let mut channels = Vec::with_capacity(Observation::Window { size, one_hot }.channels());
+        // rows from farthest ahead to farthest behind, columns left to right
+        for forward in (-half..=half).rev() {
+            for right in -half..=half {
+                let location = self.grid.wrap(Location::new(
+                    head.x + forward * fx + right * rx,
+                    head.y + forward * fy + right * ry,
+                ));
+                let wall = self.is_wall(location);
+                let body = self.segments.contains(&location);
+                let food = location == self.food;
+                if one_hot {
+                    channels.extend([f32::from(wall), f32::from(body), f32::from(food)]);
+                } else if wall {
+                    channels.push(-1.0);
+                } else if body {
+                    channels.push(-0.5);
+                } else {
+                    channels.push(f32::from(food));
+                }
+            }
+        }
+        channels
+    }
+    pub fn step(&mut self, action: Action) -> StepOutcome {
+        let head = self.head();
+        let moves = [
+            (Action::Forward, self.direction),
+            (Action::Left, self.direction.left()),
+            (Action::Right, self.direction.right()),
+        ];
+        let current = self.grid.distance(head, self.food);
+        self.can_move_towards_food = moves.iter().any(|(a, d)| {
+            let next = self.grid.step(head, *d);
+            *a != action && self.is_free(next) && self.grid.distance(next, self.food) < current
+        });
+        self.direction = moves.iter().find(|(a, _)| *a == action).unwrap().1;
+        let new_head = self.grid.step(head, self.direction);
+        self.last_head = head;
+        self.death = if self.is_wall(new_head) {
+            Some(Death::Wall)
+        } else if self.segments.contains(&new_head) {
+            Some(Death::Body)
+        } else {
+            None
+        };
+        self.segments.insert(0, new_head);
+        self.collected_food = new_head == self.food;
+        if self.collected_food {
+            if !self.spawn_food() && self.death.is_none() {
+                self.death = Some(Death::Full);
+            }
+            self.turns_since_food = 0;
+            self.seen.clear();
+        } else {
+            let _ = self.segments.pop();
+            self.turns_since_food += 1;
+        }
+        if self.death.is_none() {
+            self.death = self.unproductive();
+        }
+        StepOutcome {
+            status: self.reward_status(),
+            death: self.death,
+        }
+    }
+    fn unproductive(&mut self) -> Option<Death> {
+        let limits = self.limits;
+        if limits.starvation > 0
+            && self.turns_since_food
+                > limits.starvation + limits.starvation_per_segment * self.segments.len() as i32
+        {
+            return Some(Death::Starvation);
+        }
+        if limits.detect_loops {
+            let mut hasher = DefaultHasher::new();
+            self.direction.hash(&mut hasher);
+            self.segments.hash(&mut hasher);
+            if !self.seen.insert(hasher.finish()) {
+                return Some(Death::Loop);
+            }
+        }
+        None
+    }
+    // false when body and walls cover the whole board, the food then stays where it is
+    fn spawn_food(&mut self) -> bool {
+        let (width, height) = self.grid.grid;
+        if self.segments.len() + self.walls.len() >= (width * height) as usize {
+            return false;
+        }
+        if self.start.shared_food {
+            self.food = self.shared_food();
+        } else {
+            self.food = Location::new(
+                self.rng.gen_range(0..self.grid.grid.0),
+                self.rng.gen_range(0..self.grid.grid.1),
+            );
+            while self.segments.contains(&self.food) || self.walls.contains(&self.food) {
+                self.food = Location::new(
+                    self.rng.gen_range(0..self.grid.grid.0),
+                    self.rng.gen_range(0..self.grid.grid.1),
+                );
+            }
+        }
+        self.foods += 1;
+        true
+    }
+    // the i-th food only depends on the game stream (seed, generation, trial) and i:
+    // it is drawn from its own spot of a side stream, and a taken cell moves on to
+    // the next free one in row order, so no genome shifts another genome's sequence
+    fn shared_food(&self) -> Location {
+        let mut rng = self.rng.clone();
+        rng.set_stream(rng.get_stream() | 1 << 63);
+        rng.set_word_pos(self.foods as u128 * Self::WORDS_PER_FOOD);
+        let (width, height) = self.grid.grid;
+        let first = rng.gen_range(0..height) * width + rng.gen_range(0..width);
+        let cells = width * height;
+        (0..cells)
+            .map(|i| {
+                let cell = (first + i) % cells;
+                Location::new(cell % width, cell / width)
+            })
+            .find(|l| !self.segments.contains(l) && !self.walls.contains(l))
+            .unwrap_or(Location::new(first % width, first / width))
+    }
+    pub fn reward_status(&self) -> RewardStatus {
+        let mut status = RewardStatus {
+            can_move_towards_food: true,
+            moved_towards_food: false,
+            collected_food: false,
+        };
+        if self.collected_food {
+            status.collected_food = true;
+        } else {
+            let last = self.last_head;
+            let current = self.head();
+            let prev = self.grid.distance(self.food, last);
+            let now = self.grid.distance(self.food, current);
+            if prev >= now {
+                status.moved_towards_food = true;
+            }
+            status.can_move_towards_food = self.can_move_towards_food;
+        }
+        status
+    }
+}
+#[derive(Copy, Clone, Default, Ord, PartialOrd, Eq, PartialEq, Hash, Debug)]
+pub struct Location {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Location {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+    pub fn step(self, direction: Direction) -> Self {
+        let (dx, dy) = direction.offset();
+        Self::new(self.x + dx, self.y + dy)
+    }
+}
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Serialize, Deserialize)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+impl Direction {
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+        }
+    }
+    pub fn left(self) -> Self {
+        match self {
+            Direction::Left => Direction::Down,
+            Direction::Right => Direction::Up,
+            Direction::Up => Direction::Left,
+            Direction::Down => Direction::Right,
+        }
+    }
+    pub fn right(self) -> Self {
+        match self {
+            Direction::Left => Direction::Up,
+            Direction::Right => Direction::Down,
+            Direction::Up => Direction::Right,
+            Direction::Down => Direction::Left,
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    // 3 segments with the head at (4, 5) facing right, food 10 cells ahead
+    fn sim(width: i32, height: i32) -> SnakeSim {
+        let start = Start {
+            length: 3,
+            position: Some(Location::new(4, 5)),
+            ..Start::default()
+        };
+        SnakeSim::new(
+            GameGrid::new(width, height),
+            start,
+            GameRng::seed_from_u64(1),
+        )
+    }
+    fn locations(cells: &[(i32, i32)]) -> Vec<Location> {
+        cells.iter().map(|(x, y)| Location::new(*x, *y)).collect()
+    }
+    #[test]
+    fn reset_restores_the_opening() {
+        let mut sim = sim(20, 10);
+        assert_eq!(sim.segments, locations(&[(4, 5), (3, 5), (2, 5)]));
+        assert_eq!(sim.direction, Direction::Right);
+        assert_eq!(sim.food, Location::new(14, 5));
+        sim.step(Action::Left);
+        sim.step(Action::Left);
+        sim.reset(GameRng::seed_from_u64(1));
+        assert_eq!(sim.segments, locations(&[(4, 5), (3, 5), (2, 5)]));
+        assert_eq!(sim.direction, Direction::Right);
+        assert_eq!(sim.food, Location::new(14, 5));
+        assert_eq!(sim.death, None);
+    }
+    #[test]
+    fn neighbours_see_free_cells_and_food() {
+        let sim = sim(20, 10);
+        assert_eq!(sim.observe().channels, vec![1.0, 1.0, 1.0, 0.0, 0.0, 1.0]);
+    }
+    #[test]
+    fn neighbours_see_edges_and_body() {
+        let mut sim = sim(20, 10);
+        // heading up along the left edge with the body curling to the right
+        sim.segments = locations(&[(0, 5), (0, 6), (1, 6), (1, 5), (1, 4)]);
+        sim.direction = Direction::Up;
+        let neighbours = sim.neighbours();
+        assert!(!neighbours.can_move_left);
+        assert!(!neighbours.can_move_right);
+        assert!(neighbours.can_move_forward);
+        assert!(neighbours.is_food_right);
+    }
+    #[test]
+    fn leaving_the_grid_is_a_wall_death() {
+        let mut sim = sim(20, 10);
+        sim.segments = locations(&[(4, 0), (3, 0), (2, 0)]);
+        let step = sim.step(Action::Left);
+        assert_eq!(step.death, Some(Death::Wall));
+        assert!(!step.alive());
+    }
+    #[test]
+    fn turning_into_the_body_is_a_body_death() {
+        let mut sim = sim(20, 10);
+        sim.segments = locations(&[(5, 5), (5, 6), (4, 6), (4, 5), (4, 4)]);
+        sim.direction = Direction::Up;
+        assert_eq!(sim.step(Action::Left).death, Some(Death::Body));
+    }
+    #[test]
+    fn moving_away_from_food_keeps_the_length() {
+        let mut sim = sim(20, 10);
+        let step = sim.step(Action::Forward);
+        assert!(step.alive());
+        assert!(step.status.moved_towards_food);
+        assert_eq!(sim.segments, locations(&[(5, 5), (4, 5), (3, 5)]));
+        let step = sim.step(Action::Left);
+        assert!(!step.status.moved_towards_food);
+        assert!(step.status.can_move_towards_food);
+    }
+    #[test]
+    fn eating_grows_and_respawns_food_off_the_body() {
+        // a one row board leaves a single free cell once the snake has eaten
+        let mut sim = sim(5, 1);
+        sim.segments = locations(&[(2, 0), (1, 0), (0, 0)]);
+        sim.food = Location::new(3, 0);
+        let step = sim.step(Action::Forward);
+        assert!(step.status.collected_food);
+        assert_eq!(sim.segments.len(), 4);
+        assert_eq!(sim.food, Location::new(4, 0));
+    }
+    #[test]
+    fn shared_food_does_not_depend_on_other_draws() {
+        let start = Start {
+            length: 3,
+            position: Some(Location::new(4, 5)),
+            random_food: true,
+            shared_food: true,
+            ..Start::default()
+        };
+        let mut a = SnakeSim::new(GameGrid::new(20, 10), start, GameRng::seed_from_u64(7));
+        let mut b = a.clone();
+        let sequence = |sim: &mut SnakeSim| {
+            (0..5)
+                .map(|_| {
+                    sim.spawn_food();
+                    sim.food
+                })
+                .collect::<Vec<_>>()
+        };
+        let foods = sequence(&mut a);
+        // b has the first spot under its body and used its stream for something else
+        b.segments.push(foods[0]);
+        let _ = b.rng.gen::<u64>();
+        let shifted = sequence(&mut b);
+        assert_ne!(shifted[0], foods[0]);
+        assert!(!b.segments.contains(&shifted[0]));
+        assert_eq!(shifted[1..], foods[1..]);
+    }
+    #[test]
+    fn starvation_ends_a_game_without_food() {
+        let mut sim = sim(20, 10);
+        sim.limits.starvation = 1;
+        sim.limits.starvation_per_segment = 1;
+        // 1 + 1 * 3 segments allows 4 turns without food
+        for _ in 0..4 {
+            assert!(sim.step(Action::Forward).alive());
+        }
+        assert_eq!(sim.step(Action::Forward).death, Some(Death::Starvation));
+    }
+    #[test]
+    fn eating_resets_the_starvation_budget() {
+        let mut sim = sim(20, 10);
+        sim.limits.starvation = 2;
+        sim.food = Location::new(6, 5);
+        sim.step(Action::Forward);
+        assert!(sim.step(Action::Forward).status.collected_food);
+        assert_eq!(sim.turns_since_food, 0);
+    }
+    #[test]
+    fn circling_is_a_loop_death() {
+        let mut sim = sim(20, 10);
+        sim.segments.truncate(2);
+        sim.limits.detect_loops = true;
+        // a two cell snake turning right goes round a 2x2 square every 4 turns
+        for _ in 0..4 {
+            assert!(sim.step(Action::Right).alive());
+        }
+        assert_eq!(sim.step(Action::Right).death, Some(Death::Loop));
+    }
+    #[test]
+    fn limits_off_never_end_a_game() {
+        let mut sim = sim(20, 10);
+        sim.segments.truncate(2);
+        for _ in 0..100 {
+            assert!(sim.step(Action::Right).alive());
+        }
+    }
+    #[test]
+    fn rays_measure_wall_body_and_food() {
+        let mut sim = sim(20, 10);
+        sim.observation = Observation::Rays;
+        let channels = sim.observe().channels;
+        assert_eq!(channels.len(), RAYS.len() * 3);
+        // straight ahead: edge 16 cells away, food 10
+        assert_eq!(channels[0..3], [1.0 / 16.0, 0.0, 0.1]);
+        // straight back: body right behind the head, edge 5 cells away
+        assert_eq!(channels[12..15], [1.0 / 5.0, 1.0, 0.0]);
+        // to the right (down): edge 5 cells away, nothing else
+        assert_eq!(channels[6..9], [1.0 / 5.0, 0.0, 0.0]);
+    }
+    #[test]
+    fn rays_turn_with_the_heading() {
+        let mut sim = sim(20, 10);
+        sim.observation = Observation::Rays;
+        sim.segments = locations(&[(4, 5), (4, 6), (4, 7)]);
+        sim.direction = Direction::Up;
+        let channels = sim.observe().channels;
+        // ahead is up now, the food to the right
+        assert_eq!(channels[0..3], [1.0 / 6.0, 0.0, 0.0]);
+        assert_eq!(channels[6..9], [1.0 / 16.0, 0.0, 0.1]);
+    }
+    #[test]
+    fn window_is_rotated_around_the_head() {
+        let mut sim = sim(20, 10);
+        sim.segments = locations(&[(4, 0), (3, 0), (2, 0)]);
+        sim.food = Location::new(5, 1);
+        sim.observation = Observation::Window {
+            size: 3,
+            one_hot: false,
+        };
+        // rows run from ahead (x 5) to behind (x 3), columns from left (up) to right
+        assert_eq!(
+            sim.observe().channels,
+            vec![-1.0, 0.0, 1.0, -1.0, -0.5, 0.0, -1.0, -0.5, 0.0]
+        );
+    }
+    #[test]
+    fn one_hot_window_has_three_channels_per_cell() {
+        let mut sim = sim(20, 10);
+        let observation = Observation::Window {
+            size: 3,
+            one_hot: true,
+        };
+        sim.observation = observation;
+        let channels = sim.observe().channels;
+        assert_eq!(channels.len(), observation.channels());
+        // the cell behind the head is body only
+        assert_eq!(channels[21..24], [0.0, 1.0, 0.0]);
+    }
+    #[test]
+    fn one_segment_snake_can_step() {
+        let mut sim = sim(20, 10);
+        sim.start.length = 1;
+        sim.reset(GameRng::seed_from_u64(1));
+        assert_eq!(sim.segments, locations(&[(4, 5)]));
+        sim.step(Action::Forward);
+        assert_eq!(sim.segments, locations(&[(5, 5)]));
+        assert_eq!(sim.last_head, Location::new(4, 5));
+        assert_eq!(sim.death, None);
+    }
+    #[test]
+    fn fixed_start_and_food_must_fit() {
+        let grid = GameGrid::new(20, 10);
+        let walls = HashSet::from([Location::new(10, 5)]);
+        let mut start = Start {
+            length: 3,
+            position: Some(Location::new(4, 5)),
+            ..Start::default()
+        };
+        assert!(start.fits(grid, &walls));
+        start.position = Some(Location::new(1, 5));
+        assert!(!start.fits(grid, &walls));
+        start.position = Some(Location::new(11, 5));
+        assert!(!start.fits(grid, &walls));
+        start.position = Some(Location::new(4, 5));
+        for (food, fits) in [
+            ((14, 5), true),
+            ((3, 5), false),
+            ((10, 5), false),
+            ((20, 5), false),
+        ] {
+            start.food = Some(Location::new(food.0, food.1));
+            assert_eq!(start.food_fits(grid, &walls), fits, "{:?}", food);
+        }
+    }
+    #[test]
+    fn walls_block_moves_and_kill() {
+        let mut sim = sim(20, 10);
+        sim.walls = HashSet::from([Location::new(5, 5)]);
+        assert!(!sim.neighbours().can_move_forward);
+        assert!(sim.neighbours().can_move_left);
+        assert_eq!(sim.step(Action::Forward).death, Some(Death::Wall));
+    }
+    #[test]
+    fn food_never_respawns_on_a_wall() {
+        // after eating only (5, 0) is neither body nor wall
+        let mut sim = sim(6, 1);
+        sim.walls = HashSet::from([Location::new(4, 0)]);
+        sim.segments = locations(&[(2, 0), (1, 0), (0, 0)]);
+        sim.food = Location::new(3, 0);
+        assert!(sim.step(Action::Forward).status.collected_food);
+        assert_eq!(sim.food, Location::new(5, 0));
+    }
+    #[test]
+    fn rays_and_window_see_walls() {
+        let mut sim = sim(20, 10);
+        sim.walls = HashSet::from([Location::new(8, 5)]);
+        sim.observation = Observation::Rays;
+        // straight ahead the wall 4 cells away hides the food behind it
+        assert_eq!(sim.observe().channels[0..3], [0.25, 0.0, 0.0]);
+        sim.observation = Observation::Window {
+            size: 3,
+            one_hot: false,
+        };
+        sim.walls = HashSet::from([Location::new(5, 5)]);
+        let walls = sim
+            .observe()
+            .channels
+            .iter()
+            .filter(|c| **c == -1.0)
+            .count();
+        assert_eq!(walls, 1);
+    }
+    #[test]
+    fn random_start_needs_a_clear_placement() {
+        // a 3 x 2 interior inside a ring of walls
+        let grid = GameGrid::new(5, 4);
+        let walls = (0..5)
+            .flat_map(|x| [Location::new(x, 0), Location::new(x, 3)])
+            .chain((0..4).flat_map(|y| [Location::new(0, y), Location::new(4, y)]))
+            .collect::<HashSet<_>>();
+        let mut start = Start {
+            length: 3,
+            random: true,
+            ..Start::default()
+        };
+        assert!(start.fits(grid, &walls));
+        start.length = 4;
+        assert!(!start.fits(grid, &walls));
+        start.length = 3;
+        let mut sim = SnakeSim::with_walls(grid, walls.clone(), start, GameRng::seed_from_u64(1));
+        for seed in 0..20 {
+            sim.reset(GameRng::seed_from_u64(seed));
+            assert!(sim.segments.iter().all(|l| !walls.contains(l)));
+        }
+    }
+    fn wrapped(width: i32, height: i32) -> SnakeSim {
+        let mut sim = sim(width, height);
+        sim.grid.wrap = true;
+        sim
+    }
+    #[test]
+    fn wrapping_steps_back_onto_the_board() {
+        let mut grid = GameGrid::new(20, 10);
+        assert_eq!(
+            grid.step(Location::new(19, 0), Direction::Right),
+            Location::new(20, 0)
+        );
+        grid.wrap = true;
+        assert_eq!(
+            grid.step(Location::new(19, 0), Direction::Right),
+            Location::new(0, 0)
+        );
+        assert_eq!(
+            grid.step(Location::new(0, 0), Direction::Up),
+            Location::new(0, 9)
+        );
+        assert_eq!(grid.wrap(Location::new(-21, 23)), Location::new(19, 3));
+    }
+    #[test]
+    fn wrapped_edges_do_not_kill() {
+        let mut sim = wrapped(20, 10);
+        sim.segments = locations(&[(4, 0), (3, 0), (2, 0)]);
+        let step = sim.step(Action::Left);
+        assert!(step.alive());
+        assert_eq!(sim.head(), Location::new(4, 9));
+        assert!(sim.neighbours().can_move_forward);
+    }
+    #[test]
+    fn wrapped_delta_takes_the_short_way() {
+        let mut grid = GameGrid::new(20, 10);
+        let (a, b) = (Location::new(1, 1), Location::new(18, 9));
+        assert_eq!(grid.delta(a, b), (17, 8));
+        grid.wrap = true;
+        assert_eq!(grid.delta(a, b), (-3, -2));
+        assert_eq!(grid.delta(b, a), (3, 2));
+        assert_eq!(grid.distance(a, b), 13f32.sqrt());
+    }
+    #[test]
+    fn wrapped_rays_go_once_around_without_a_wall() {
+        let mut sim = wrapped(20, 10);
+        sim.observation = Observation::Rays;
+        let channels = sim.observe().channels;
+        // straight ahead: no edge, food 10 cells away, the tail 18 cells around the board
+        assert_eq!(channels[0..3], [0.0, 1.0 / 18.0, 0.1]);
+        // straight back: the body right behind the head and the food 10 cells the other way round
+        assert_eq!(channels[12..15], [0.0, 1.0, 0.1]);
+        assert!(channels.iter().step_by(3).all(|wall| *wall == 0.0));
+    }
+    #[test]
+    fn filling_the_board_ends_the_game() {
+        let mut sim = sim(3, 1);
+        sim.segments = locations(&[(1, 0), (0, 0)]);
+        sim.food = Location::new(2, 0);
+        let step = sim.step(Action::Forward);
+        assert!(step.status.collected_food);
+        assert_eq!(step.death, Some(Death::Full));
+        assert_eq!(sim.segments.len(), 3);
+    }
+    #[test]
+    fn random_start_too_long_for_the_board_keeps_the_fixed_start() {
+        let start = Start {
+            length: 5,
+            position: Some(Location::new(4, 1)),
+            random: true,
+            ..Start::default()
+        };
+        let sim = SnakeSim::new(GameGrid::new(3, 3), start, GameRng::seed_from_u64(1));
+        assert_eq!(sim.head(), Location::new(4, 1));
+        assert_eq!(sim.segments.len(), 5);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::neat::environment::Environment;
+use crate::neat::network::Network;
+use crate::neat::task::{Outcome, Task};
+use crate::neat::{Fitness, GameRng};
+use rand::Rng;
+
+pub struct CartPole {
+    pub max_steps: i32,
+}
+impl Default for CartPole {
+    fn default() -> Self {
+        Self { max_steps: 10_000 }
+    }
+}
+impl CartPole {
+    const GRAVITY: f32 = 9.8;
+    const CART_MASS: f32 = 1.0;
+    const POLE_MASS: f32 = 0.1;
+    const HALF_LENGTH: f32 = 0.5;
+    const FORCE: f32 = 10.0;
+    const TAU: f32 = 0.02;
+    const TRACK_LIMIT: f32 = 2.4;
+    const ANGLE_LIMIT: f32 = 12.0 * std::f32::consts::PI / 180.0;
+}
+impl Task for CartPole {
+    fn name(&self) -> &str {
+        "cart-pole"
+    }
+    fn input_size(&self) -> usize {
+        4
+    }
+    fn output_size(&self) -> usize {
+        1
+    }
+    fn evaluate(&self, network: &Network, environment: &Environment, rng: &mut GameRng) -> Outcome {
+        let mut x = rng.gen_range(-0.05..0.05);
+        let mut x_dot = rng.gen_range(-0.05..0.05);
+        let mut theta = rng.gen_range(-0.05..0.05);
+        let mut theta_dot = rng.gen_range(-0.05..0.05);
+        let total_mass = Self::CART_MASS + Self::POLE_MASS;
+        let pole_mass_length = Self::POLE_MASS * Self::HALF_LENGTH;
+        let mut activations = network.activations();
+        let mut steps = 0;
+        while steps < self.max_steps {
+            let input = [
+                x / Self::TRACK_LIMIT,
+                x_dot / 2.0,
+                theta / Self::ANGLE_LIMIT,
+                theta_dot / 2.0,
+            ];
+            network.activate(&mut activations, &input);
+            let force = if activations.outputs(environment)[0] > 0.5 {
+                Self::FORCE
+            } else {
+                -Self::FORCE
+            };
+            let (sin, cos) = theta.sin_cos();
+            let temp = (force + pole_mass_length * theta_dot * theta_dot * sin) / total_mass;
+            let theta_acc = (Self::GRAVITY * sin - cos * temp)
+                / (Self::HALF_LENGTH * (4.0 / 3.0 - Self::POLE_MASS * cos * cos / total_mass));
+            let x_acc = temp - pole_mass_length * theta_acc * cos / total_mass;
+            x += Self::TAU * x_dot;
+            x_dot += Self::TAU * x_acc;
+            theta += Self::TAU * theta_dot;
+            theta_dot += Self::TAU * theta_acc;
+            if x.abs() > Self::TRACK_LIMIT || theta.abs() > Self::ANGLE_LIMIT {
+                break;
+            }
+            steps += 1;
+        }
+        Outcome {
+            fitness: steps as Fitness,
+            steps: steps as f32,
+            ..Outcome::default()
+        }
+    }
+    fn solved(&self, fitness: Fitness) -> bool {
+        fitness >= self.max_steps as Fitness
+    }
+}
@@ -0,0 +1,120 @@
+use crate::neat::environment::Environment;
+use crate::neat::network::Network;
+use crate::neat::task::{Outcome, Task};
+use crate::neat::{Fitness, GameRng};
+
+// two poles on one cart with velocities given (markovian), integrated with rk4
+pub struct DoublePole {
+    pub max_steps: i32,
+}
+impl Default for DoublePole {
+    fn default() -> Self {
+        Self { max_steps: 100_000 }
+    }
+}
+type State = [f32; 6];
+impl DoublePole {
+    const GRAVITY: f32 = -9.8;
+    const CART_MASS: f32 = 1.0;
+    const POLE_MASS: [f32; 2] = [0.1, 0.01];
+    const HALF_LENGTH: [f32; 2] = [0.5, 0.05];
+    const POLE_FRICTION: f32 = 0.000002;
+    const FORCE: f32 = 10.0;
+    const TAU: f32 = 0.01;
+    const TRACK_LIMIT: f32 = 2.4;
+    const ANGLE_LIMIT: f32 = 36.0 * std::f32::consts::PI / 180.0;
+    fn derivatives(force: f32, state: &State) -> State {
+        let mut derivs = [0.0; 6];
+        let mut fi = 0.0;
+        let mut mi = 0.0;
+        let mut terms = [(0.0, 0.0, 0.0); 2];
+        for pole in 0..2 {
+            let theta = state[2 + pole * 2];
+            let theta_dot = state[3 + pole * 2];
+            let (sin, cos) = theta.sin_cos();
+            let g_sin = Self::GRAVITY * sin;
+            let ml = Self::HALF_LENGTH[pole] * Self::POLE_MASS[pole];
+            let friction = Self::POLE_FRICTION * theta_dot / ml;
+            fi += ml * theta_dot * theta_dot * sin
+                + 0.75 * Self::POLE_MASS[pole] * cos * (friction + g_sin);
+            mi += Self::POLE_MASS[pole] * (1.0 - 0.75 * cos * cos);
+            terms[pole] = (cos, g_sin, friction);
+        }
+        derivs[0] = state[1];
+        derivs[1] = (force + fi) / (mi + Self::CART_MASS);
+        for (pole, (cos, g_sin, friction)) in terms.iter().enumerate() {
+            derivs[2 + pole * 2] = state[3 + pole * 2];
+            derivs[3 + pole * 2] =
+                -0.75 * (derivs[1] * cos + g_sin + friction) / Self::HALF_LENGTH[pole];
+        }
+        derivs
+    }
+    fn rk4(force: f32, state: &State) -> State {
+        let offset = |state: &State, derivs: &State, scale: f32| {
+            let mut next = *state;
+            for i in 0..6 {
+                next[i] += derivs[i] * scale;
+            }
+            next
+        };
+        let k1 = Self::derivatives(force, state);
+        let k2 = Self::derivatives(force, &offset(state, &k1, Self::TAU / 2.0));
+        let k3 = Self::derivatives(force, &offset(state, &k2, Self::TAU / 2.0));
+        let k4 = Self::derivatives(force, &offset(state, &k3, Self::TAU));
+        let mut next = *state;
+        for i in 0..6 {
+            next[i] += Self::TAU / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]);
+        }
+        next
+    }
+}
+impl Task for DoublePole {
+    fn name(&self) -> &str {
+        "double-pole"
+    }
+    fn input_size(&self) -> usize {
+        6
+    }
+    fn output_size(&self) -> usize {
+        1
+    }
+    fn evaluate(
+        &self,
+        network: &Network,
+        environment: &Environment,
+        _rng: &mut GameRng,
+    ) -> Outcome {
+        let mut state: State = [0.0, 0.0, 4.0_f32.to_radians(), 0.0, 0.0, 0.0];
+        let mut activations = network.activations();
+        let mut steps = 0;
+        while steps < self.max_steps {
+            let input = [
+                state[0] / 4.8,
+                state[1] / 2.0,
+                state[2] / 0.52,
+                state[3] / 2.0,
+                state[4] / 0.52,
+                state[5] / 2.0,
+            ];
+            network.activate(&mut activations, &input);
+            let force = (activations.outputs(environment)[0] - 0.5) * Self::FORCE * 2.0;
+            // two integration steps per network activation
+            state = Self::rk4(force, &Self::rk4(force, &state));
+            if state[0].abs() > Self::TRACK_LIMIT
+                || state[2].abs() > Self::ANGLE_LIMIT
+                || state[4].abs() > Self::ANGLE_LIMIT
+            {
+                break;
+            }
+            steps += 1;
+        }
+        Outcome {
+            fitness: steps as Fitness,
+            steps: steps as f32,
+            ..Outcome::default()
+        }
+    }
+    fn solved(&self, fitness: Fitness) -> bool {
+        fitness >= self.max_steps as Fitness
+    }
+}
@@ -0,0 +1,18 @@
+use crate::config::Config;
+use crate::neat::task::Task;
+
+pub mod cart_pole;
+pub mod double_pole;
+pub mod xor;
+
+pub const NAMES: [&str; 4] = ["snake", "xor", "cart-pole", "double-pole"];
+
+pub fn by_name(name: &str, config: &Config) -> Option<Box<dyn Task>> {
+    match name {
+        "snake" => Some(Box::new(config.snake_task())),
+        "xor" => Some(Box::new(xor::Xor {})),
+        "cart-pole" => Some(Box::new(cart_pole::CartPole::default())),
+        "double-pole" => Some(Box::new(double_pole::DoublePole::default())),
+        _ => None,
+    }
+}
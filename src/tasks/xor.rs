@@ -0,0 +1,47 @@
+use crate::neat::environment::Environment;
+use crate::neat::network::Network;
+use crate::neat::task::{Outcome, Task};
+use crate::neat::{Fitness, GameRng};
+
+pub struct Xor {}
+impl Xor {
+    const CASES: [([f32; 2], f32); 4] = [
+        ([0.0, 0.0], 0.0),
+        ([0.0, 1.0], 1.0),
+        ([1.0, 0.0], 1.0),
+        ([1.0, 1.0], 0.0),
+    ];
+}
+impl Task for Xor {
+    fn name(&self) -> &str {
+        "xor"
+    }
+    fn input_size(&self) -> usize {
+        2
+    }
+    fn output_size(&self) -> usize {
+        1
+    }
+    fn evaluate(
+        &self,
+        network: &Network,
+        environment: &Environment,
+        _rng: &mut GameRng,
+    ) -> Outcome {
+        let mut error = 0.0;
+        for (input, expected) in Self::CASES.iter() {
+            let mut activations = network.activations();
+            network.activate(&mut activations, input);
+            error += (activations.outputs(environment)[0] - expected).abs();
+        }
+        // squared to widen the gap between close and exact solutions
+        Outcome {
+            fitness: (4.0 - error).powi(2),
+            steps: Self::CASES.len() as f32,
+            ..Outcome::default()
+        }
+    }
+    fn solved(&self, fitness: Fitness) -> bool {
+        fitness > 15.5
+    }
+}